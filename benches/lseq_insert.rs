@@ -0,0 +1,52 @@
+extern crate crdts;
+use crdts::lseq::{ident::AllocStrategy, LSeq};
+use crdts::CmRDT;
+use rand::Rng;
+use std::time::Instant;
+
+// Cost of the LSeq operations that depend on how elements are stored, run it with
+// `cargo bench --bench lseq_insert`.
+//
+// Elements are kept in a `Vec` sorted by identifier: lookups by identifier are binary
+// searches, positional access is an index, and an insert shifts the elements after it.
+
+const ELEMENTS: usize = 50_000;
+
+fn measure<T>(name: &str, f: impl FnOnce() -> T) -> T {
+    let start = Instant::now();
+    let result = f();
+    println!("{:<20}{:?}", format!("{}:", name), start.elapsed());
+    result
+}
+
+fn main() {
+    let mut rng = rand::thread_rng();
+    println!("{} elements\n", ELEMENTS);
+
+    let mut site = LSeq::with_strategy(0u8, AllocStrategy::Alternating);
+    let ops = measure("random inserts", || {
+        (0..ELEMENTS)
+            .map(|i| site.insert_index(rng.gen_range(0, site.len() + 1), i as u32))
+            .collect::<Vec<_>>()
+    });
+
+    let mut replica = LSeq::new(1u8);
+    measure("remote apply", || {
+        for op in ops.iter().cloned() {
+            replica.apply(op);
+        }
+    });
+
+    let ixs: Vec<usize> = (0..ELEMENTS).map(|_| rng.gen_range(0, ELEMENTS)).collect();
+    let cursors: Vec<_> = ixs.iter().map(|ix| site.cursor(*ix).unwrap()).collect();
+    measure("positional get", || {
+        ixs.iter().filter_map(|ix| site.get(*ix)).count()
+    });
+    measure("identifier lookup", || {
+        cursors
+            .iter()
+            .filter_map(|cursor| replica.resolve_cursor(cursor))
+            .count()
+    });
+    measure("iter equality", || assert!(site.iter().eq(replica.iter())));
+}
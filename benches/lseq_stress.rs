@@ -0,0 +1,58 @@
+extern crate crdts;
+use crdts::lseq::{LSeq, Op};
+use crdts::CmRDT;
+use rand::Rng;
+use std::time::{Duration, Instant};
+
+// A stress run for large documents, run it with `cargo bench --bench lseq_stress`.
+//
+// Each scenario edits a document at one site and replays the ops at a second site, then
+// reports the cost of `apply` and `iter` along with how deep the identifiers have grown.
+
+const EDITS: usize = 10_000;
+
+fn report(name: &str, site: &LSeq<u32, u8>, ops: Vec<Op<u32, u8>>, edit_time: Duration) {
+    let mut replica = LSeq::new(1);
+    let start = Instant::now();
+    for op in ops {
+        replica.apply(op);
+    }
+    let apply_time = start.elapsed();
+
+    let start = Instant::now();
+    let sum: u64 = replica.iter().map(|val| *val as u64).sum();
+    let iter_time = start.elapsed();
+    assert_eq!(sum, site.iter().map(|val| *val as u64).sum::<u64>());
+
+    println!("{}:", name);
+    println!("  local edits:    {:?}", edit_time);
+    println!("  remote apply:   {:?}", apply_time);
+    println!("  iter:           {:?}", iter_time);
+    println!("  max id depth:   {}", site.max_identifier_depth());
+}
+
+fn main() {
+    let mut site = LSeq::new(0);
+    let start = Instant::now();
+    let ops = (0..EDITS).map(|i| site.append(i as u32)).collect();
+    report("sequential appends", &site, ops, start.elapsed());
+
+    let mut rng = rand::thread_rng();
+    let mut site = LSeq::new(0);
+    let start = Instant::now();
+    let ops: Vec<_> = (0..EDITS)
+        .map(|i| {
+            let ix = rng.gen_range(0, site.len() + 1);
+            site.insert_index(ix, i as u32)
+        })
+        .collect();
+    report("random inserts", &site, ops.clone(), start.elapsed());
+
+    // rebalancing moves every element to a fresh identifier, the moves are replayed
+    // after the inserts they move
+    let start = Instant::now();
+    let moves = site.rebalance();
+    let edit_time = start.elapsed();
+    let ops = ops.into_iter().chain(moves).collect();
+    report("random inserts, rebalanced", &site, ops, edit_time);
+}
@@ -0,0 +1,83 @@
+extern crate crdts;
+use crdts::{Causal, CmRDT, CvRDT, Dot, MVReg};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
+
+// Allocations made by `merge` and `forget` on registers holding large values, run it with
+// `cargo bench --bench mvreg_merge`.
+//
+// Both work on the values in place, so the values themselves are never copied: the bytes
+// allocated stay small next to the size of the values held by the registers.
+
+const REPLICAS: u8 = 16;
+const VALUE_BYTES: usize = 64 * 1024;
+
+struct CountingAlloc;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+static ALLOCATED_BYTES: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        ALLOCATED_BYTES.fetch_add(layout.size(), Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+fn measure<T>(name: &str, f: impl FnOnce() -> T) -> T {
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed);
+    let bytes = ALLOCATED_BYTES.load(Ordering::Relaxed);
+    let start = Instant::now();
+    let result = f();
+    let elapsed = start.elapsed();
+
+    println!("{}:", name);
+    println!("  time:           {:?}", elapsed);
+    println!(
+        "  allocations:    {}",
+        ALLOCATIONS.load(Ordering::Relaxed) - allocations
+    );
+    println!(
+        "  bytes:          {}",
+        ALLOCATED_BYTES.load(Ordering::Relaxed) - bytes
+    );
+    result
+}
+
+fn main() {
+    // every replica writes a large value concurrently
+    let replicas: Vec<MVReg<Vec<u8>, u8>> = (0..REPLICAS)
+        .map(|actor| {
+            let mut reg = MVReg::new();
+            let op = reg.write(
+                vec![actor; VALUE_BYTES],
+                reg.read_ctx().derive_add_ctx(actor),
+            );
+            reg.apply(op);
+            reg
+        })
+        .collect();
+    println!("{} concurrent values of {} bytes\n", REPLICAS, VALUE_BYTES);
+
+    let mut merged = MVReg::new();
+    measure("merge", || {
+        for reg in replicas {
+            merged.merge(reg);
+        }
+    });
+    assert_eq!(merged.read_iter().val.count(), REPLICAS as usize);
+
+    // forgetting half the actors' history trims their clocks and drops their values
+    let forgotten = (0..REPLICAS / 2).map(|actor| Dot::new(actor, 1)).collect();
+    measure("forget", || merged.forget(&forgotten));
+    assert_eq!(merged.read_iter().val.count(), (REPLICAS / 2) as usize);
+}
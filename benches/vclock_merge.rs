@@ -0,0 +1,92 @@
+extern crate crdts;
+use crdts::{CvRDT, Dot, VClock};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
+
+// Allocations made by `merge` and `merge_ref` when a peer's clock mostly holds actors we
+// already know, run it with `cargo bench --bench vclock_merge`.
+//
+// `merge` needs an owned clock, so a caller holding a borrowed one clones every actor
+// first. `merge_ref` only clones the actors that are new to the local clock.
+
+const ACTORS: usize = 1_000;
+const NEW_ACTORS: usize = 10;
+const ROUNDS: usize = 100;
+
+struct CountingAlloc;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+static ALLOCATED_BYTES: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        ALLOCATED_BYTES.fetch_add(layout.size(), Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+fn measure<T>(name: &str, f: impl FnOnce() -> T) -> T {
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed);
+    let bytes = ALLOCATED_BYTES.load(Ordering::Relaxed);
+    let start = Instant::now();
+    let result = f();
+    let elapsed = start.elapsed();
+
+    println!("{}:", name);
+    println!("  time:           {:?}", elapsed);
+    println!(
+        "  allocations:    {}",
+        ALLOCATIONS.load(Ordering::Relaxed) - allocations
+    );
+    println!(
+        "  bytes:          {}",
+        ALLOCATED_BYTES.load(Ordering::Relaxed) - bytes
+    );
+    result
+}
+
+fn actor(i: usize) -> String {
+    format!("replica-{:06}", i)
+}
+
+fn main() {
+    let local: VClock<String> = (0..ACTORS).map(|i| Dot::new(actor(i), 1)).collect();
+    // the peer has advanced every known actor and seen a few new ones
+    let remote: BTreeMap<String, u64> = (0..ACTORS + NEW_ACTORS).map(|i| (actor(i), 2)).collect();
+    let remote_clock: VClock<String> = remote
+        .iter()
+        .map(|(actor, counter)| Dot::new(actor.clone(), *counter))
+        .collect();
+    println!(
+        "{} rounds merging {} actors, {} of them new\n",
+        ROUNDS,
+        ACTORS + NEW_ACTORS,
+        NEW_ACTORS
+    );
+
+    let mut clocks: Vec<_> = (0..ROUNDS).map(|_| local.clone()).collect();
+    measure("merge", || {
+        for clock in clocks.iter_mut() {
+            clock.merge(remote_clock.clone());
+        }
+    });
+    assert!(clocks.iter().all(|clock| clock == &remote_clock));
+
+    let mut clocks: Vec<_> = (0..ROUNDS).map(|_| local.clone()).collect();
+    measure("merge_ref", || {
+        for clock in clocks.iter_mut() {
+            clock.merge_ref(remote.iter());
+        }
+    });
+    assert!(clocks.iter().all(|clock| clock == &remote_clock));
+}
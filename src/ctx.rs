@@ -1,6 +1,6 @@
 use serde::{Deserialize, Serialize};
 
-use crate::{Actor, CmRDT, Dot, VClock};
+use crate::{Actor, CmRDT, CvRDT, Dot, VClock};
 
 /// ReadCtx's are used to extract data from CRDT's while maintaining some causal history.
 /// You should store ReadCtx's close to where mutation is exposed to the user.
@@ -51,4 +51,85 @@ impl<V, A: Actor> ReadCtx<V, A> {
             clock: self.rm_clock.clone(),
         }
     }
+
+    /// Splits this ReadCtx into its add and rm clocks, discarding the read value
+    pub fn split(self) -> (VClock<A>, VClock<A>) {
+        (self.add_clock, self.rm_clock)
+    }
+}
+
+impl<A: Actor> AddCtx<A> {
+    /// Combines two AddCtx's into one whose clock is the merge of both clocks.
+    ///
+    /// The combined context keeps the actor of `self`, with its dot advanced
+    /// to the latest version of that actor seen by either context. Merging the
+    /// clocks commutes, but the choice of actor does not: `a.combine(b)` and
+    /// `b.combine(a)` have the same clock but may have different dots.
+    pub fn combine(self, other: AddCtx<A>) -> AddCtx<A> {
+        let mut clock = self.clock;
+        clock.merge(other.clock);
+        let dot = clock.dot(self.dot.actor);
+        AddCtx { clock, dot }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use alloc::vec;
+
+    #[test]
+    fn test_split() {
+        let read_ctx = ReadCtx {
+            add_clock: vec![Dot::new("A", 2), Dot::new("B", 1)]
+                .into_iter()
+                .collect(),
+            rm_clock: Dot::new("A", 1).into(),
+            val: "value",
+        };
+
+        let (add_clock, rm_clock) = read_ctx.clone().split();
+        assert_eq!(add_clock, read_ctx.add_clock);
+        assert_eq!(rm_clock, read_ctx.rm_clock);
+    }
+
+    #[test]
+    fn test_derive_rm_ctx_uses_rm_clock() {
+        let read_ctx = ReadCtx {
+            add_clock: vec![Dot::new("A", 2), Dot::new("B", 1)]
+                .into_iter()
+                .collect(),
+            rm_clock: Dot::new("A", 1).into(),
+            val: (),
+        };
+
+        assert_eq!(read_ctx.derive_rm_ctx().clock, read_ctx.rm_clock);
+    }
+
+    #[test]
+    fn test_combine() {
+        let read_a = ReadCtx {
+            add_clock: vec![Dot::new("A", 2), Dot::new("B", 1)]
+                .into_iter()
+                .collect(),
+            rm_clock: VClock::new(),
+            val: (),
+        };
+        let read_b = ReadCtx {
+            add_clock: vec![Dot::new("A", 1), Dot::new("C", 4)]
+                .into_iter()
+                .collect(),
+            rm_clock: VClock::new(),
+            val: (),
+        };
+
+        let ctx_a = read_a.derive_add_ctx("A");
+        let ctx_b = read_b.derive_add_ctx("C");
+        let combined = ctx_a.clone().combine(ctx_b.clone());
+
+        assert!(combined.clock >= ctx_a.clock);
+        assert!(combined.clock >= ctx_b.clock);
+        assert_eq!(combined.dot, Dot::new("A", 3));
+        assert_eq!(combined.clock, ctx_b.combine(ctx_a).clock);
+    }
 }
@@ -1,12 +1,18 @@
-use std::cmp::{Ordering, PartialOrd};
-use std::hash::{Hash, Hasher};
+use core::cmp::{Ordering, PartialOrd};
+use core::fmt;
+use core::hash::{Hash, Hasher};
+use core::marker::PhantomData;
 
-use serde::{Deserialize, Serialize};
+use serde::de::{self, MapAccess, SeqAccess, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
+#[cfg(feature = "std")]
 use crate::quickcheck::{Arbitrary, Gen};
 
 /// Dot is a version marker for a single actor
-#[derive(Debug, Clone, Serialize, Deserialize)]
+///
+/// Dots serialize compactly as an `(actor, counter)` tuple and display as `actor.counter`.
+#[derive(Debug, Clone)]
 pub struct Dot<A> {
     /// The actor identifier
     pub actor: A,
@@ -36,6 +42,71 @@ impl<A: Clone> Dot<A> {
 
 impl<A: Copy> Copy for Dot<A> {}
 
+impl<A: fmt::Display> fmt::Display for Dot<A> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}.{}", self.actor, self.counter)
+    }
+}
+
+impl<A: Serialize> Serialize for Dot<A> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        (&self.actor, self.counter).serialize(serializer)
+    }
+}
+
+/// Dots are also read from the `{"actor": .., "counter": ..}` map that crdts 4.x wrote to
+/// self-describing formats. Other formats encode the map and the tuple the same way.
+impl<'de, A: Deserialize<'de>> Deserialize<'de> for Dot<A> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_any(DotVisitor(PhantomData))
+        } else {
+            deserializer.deserialize_tuple(2, DotVisitor(PhantomData))
+        }
+    }
+}
+
+struct DotVisitor<A>(PhantomData<A>);
+
+#[derive(Deserialize)]
+#[serde(field_identifier, rename_all = "lowercase")]
+enum DotField {
+    Actor,
+    Counter,
+}
+
+impl<'de, A: Deserialize<'de>> Visitor<'de> for DotVisitor<A> {
+    type Value = Dot<A>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "an (actor, counter) tuple")
+    }
+
+    fn visit_seq<S: SeqAccess<'de>>(self, mut seq: S) -> Result<Dot<A>, S::Error> {
+        let actor = seq
+            .next_element()?
+            .ok_or_else(|| de::Error::invalid_length(0, &self))?;
+        let counter = seq
+            .next_element()?
+            .ok_or_else(|| de::Error::invalid_length(1, &self))?;
+        Ok(Dot { actor, counter })
+    }
+
+    fn visit_map<M: MapAccess<'de>>(self, mut map: M) -> Result<Dot<A>, M::Error> {
+        let (mut actor, mut counter) = (None, None);
+        while let Some(field) = map.next_key()? {
+            match field {
+                DotField::Actor => actor = Some(map.next_value()?),
+                DotField::Counter => counter = Some(map.next_value()?),
+            }
+        }
+        Ok(Dot {
+            actor: actor.ok_or_else(|| de::Error::missing_field("actor"))?,
+            counter: counter.ok_or_else(|| de::Error::missing_field("counter"))?,
+        })
+    }
+}
+
 impl<A: PartialEq> PartialEq for Dot<A> {
     fn eq(&self, other: &Self) -> bool {
         self.actor == other.actor && self.counter == other.counter
@@ -61,6 +132,52 @@ impl<A: PartialOrd> PartialOrd for Dot<A> {
     }
 }
 
+/// A Dot with a total order, dots are ordered by actor and dots of the same actor by
+/// counter.
+///
+/// `Dot`'s own `PartialOrd` is the causal order, under which dots of different actors are
+/// incomparable. Use `OrdDot` to sort dots or to key ordered collections by them.
+///
+/// # Examples
+/// ```
+/// use crdts::{Dot, dot::OrdDot};
+/// let mut dots: Vec<OrdDot<_>> = vec![Dot::new("B", 1), Dot::new("A", 2), Dot::new("A", 1)]
+///     .into_iter()
+///     .map(OrdDot::from)
+///     .collect();
+/// dots.sort();
+/// assert_eq!(
+///     dots.into_iter().map(Dot::from).collect::<Vec<_>>(),
+///     vec![Dot::new("A", 1), Dot::new("A", 2), Dot::new("B", 1)]
+/// );
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct OrdDot<A> {
+    /// The actor identifier
+    pub actor: A,
+    /// The current version of this actor
+    pub counter: u64,
+}
+
+impl<A> From<Dot<A>> for OrdDot<A> {
+    fn from(Dot { actor, counter }: Dot<A>) -> Self {
+        Self { actor, counter }
+    }
+}
+
+impl<A> From<OrdDot<A>> for Dot<A> {
+    fn from(OrdDot { actor, counter }: OrdDot<A>) -> Self {
+        Self { actor, counter }
+    }
+}
+
+impl<A: fmt::Display> fmt::Display for OrdDot<A> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}.{}", self.actor, self.counter)
+    }
+}
+
+#[cfg(feature = "std")]
 impl<A: Arbitrary + Clone> Arbitrary for Dot<A> {
     fn arbitrary<G: Gen>(g: &mut G) -> Self {
         Dot {
@@ -78,16 +195,66 @@ impl<A: Arbitrary + Clone> Arbitrary for Dot<A> {
     }
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "std"))]
 mod test {
     use super::*;
     use quickcheck::quickcheck;
 
+    #[test]
+    fn test_display() {
+        assert_eq!(Dot::new("A", 3).to_string(), "A.3");
+        assert_eq!(Dot::new(12, 0).to_string(), "12.0");
+    }
+
+    #[test]
+    fn test_serializes_as_tuple() {
+        let dot = Dot::new(7u8, 42);
+        assert_eq!(
+            bincode::serialize(&dot).unwrap(),
+            bincode::serialize(&(7u8, 42u64)).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_decode_4x_json() {
+        let dot: Dot<u8> = serde_json::from_str(r#"{"actor":1,"counter":2}"#).unwrap();
+        assert_eq!(dot, Dot::new(1, 2));
+        let dot: Dot<u8> = serde_json::from_str(r#"{"counter":2,"actor":1}"#).unwrap();
+        assert_eq!(dot, Dot::new(1, 2));
+
+        assert_eq!(serde_json::to_string(&dot).unwrap(), "[1,2]");
+        assert_eq!(serde_json::from_str::<Dot<u8>>("[1,2]").unwrap(), dot);
+        assert!(serde_json::from_str::<Dot<u8>>(r#"{"actor":1}"#).is_err());
+    }
+
     quickcheck! {
+        fn prop_serde_round_trip(dot: Dot<u8>) -> bool {
+            let bytes = bincode::serialize(&dot).unwrap();
+            let json = serde_json::to_string(&dot).unwrap();
+            bincode::deserialize::<Dot<u8>>(&bytes).unwrap() == dot
+                && serde_json::from_str::<Dot<u8>>(&json).unwrap() == dot
+        }
+
         fn inc_increments_only_the_counter(dot: Dot<u8>) -> bool {
             dot.inc() == Dot::new(dot.actor, dot.counter + 1)
         }
 
+        fn prop_ord_dot_orders_by_actor_then_counter(a: Dot<u8>, b: Dot<u8>) -> bool {
+            let ord = OrdDot::from(a).cmp(&OrdDot::from(b));
+            ord == (a.actor, a.counter).cmp(&(b.actor, b.counter))
+        }
+
+        fn prop_ord_dot_agrees_with_causal_order(a: Dot<u8>, b: Dot<u8>) -> bool {
+            match a.partial_cmp(&b) {
+                Some(ord) => OrdDot::from(a).cmp(&OrdDot::from(b)) == ord,
+                None => true,
+            }
+        }
+
+        fn prop_ord_dot_round_trips(dot: Dot<u8>) -> bool {
+            Dot::from(OrdDot::from(dot)) == dot
+        }
+
         fn test_partial_order(a: Dot<u8>, b: Dot<u8>) -> bool {
             let cmp_ab = a.partial_cmp(&b);
             let cmp_ba = b.partial_cmp(&a);
@@ -1,7 +1,10 @@
-use std::{error, fmt, result};
+use core::fmt;
+#[cfg(feature = "std")]
+use std::error;
 
 /// CRDT Result alias to reduce redundency in function return types
-pub(crate) type Result<T> = result::Result<T, Error>;
+#[cfg(feature = "std")]
+pub(crate) type Result<T> = core::result::Result<T, Error>;
 
 /// Possible CRDT error codes
 #[derive(Debug, PartialEq)]
@@ -14,6 +17,7 @@ pub enum Error {
     ConflictingMarker,
 }
 
+#[cfg(feature = "std")]
 impl error::Error for Error {
     fn description(&self) -> &str {
         match self {
@@ -31,7 +35,9 @@ impl error::Error for Error {
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
-            Error::ConflictingMarker => write!(f, "{}", self.to_string()),
+            Error::ConflictingMarker => {
+                write!(f, "Dot's are used exactly once for the lifetime of a CRDT")
+            }
         }
     }
 }
@@ -0,0 +1,33 @@
+use core::hash::{Hash, Hasher};
+
+const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/// A 64 bit FNV-1a hasher, stands in for `DefaultHasher` when `std` is off.
+pub(crate) struct FnvHasher(u64);
+
+impl Default for FnvHasher {
+    fn default() -> Self {
+        FnvHasher(OFFSET_BASIS)
+    }
+}
+
+impl Hasher for FnvHasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= u64::from(*byte);
+            self.0 = self.0.wrapping_mul(PRIME);
+        }
+    }
+}
+
+/// Hash a value with a fresh `FnvHasher`
+pub(crate) fn hash_of<T: Hash + ?Sized>(val: &T) -> u64 {
+    let mut hasher = FnvHasher::default();
+    val.hash(&mut hasher);
+    hasher.finish()
+}
@@ -1,7 +1,10 @@
+use std::convert::Infallible;
+
 use num_bigint::BigUint;
 use serde::{Deserialize, Serialize};
 
-use crate::{Actor, Causal, CmRDT, CvRDT, Dot, VClock};
+use crate::quickcheck::{Arbitrary, Gen};
+use crate::{Actor, Causal, CmRDT, CvRDT, DeltaCmRDT, Dot, OpId, VClock};
 
 /// `GCounter` is a grow-only witnessed counter.
 ///
@@ -23,7 +26,7 @@ use crate::{Actor, Causal, CmRDT, CvRDT, Dot, VClock};
 /// ```
 #[derive(Debug, PartialEq, Eq, Clone, Hash, Serialize, Deserialize)]
 pub struct GCounter<A: Actor> {
-    inner: VClock<A>,
+    pub(crate) inner: VClock<A>,
 }
 
 impl<A: Actor> Default for GCounter<A> {
@@ -34,24 +37,49 @@ impl<A: Actor> Default for GCounter<A> {
 
 impl<A: Actor> CmRDT for GCounter<A> {
     type Op = Dot<A>;
+    type Validation = Infallible;
 
     fn apply(&mut self, op: Self::Op) {
         self.inner.apply(op)
     }
 }
 
+impl<A: Actor> OpId<A> for GCounter<A> {
+    fn op_id(op: &Self::Op) -> Dot<A> {
+        op.clone()
+    }
+}
+
 impl<A: Actor> CvRDT for GCounter<A> {
     fn merge(&mut self, other: Self) {
         self.inner.merge(other.inner);
     }
 }
 
-impl<A: Actor> Causal<A> for GCounter<A> {
-    fn forget(&mut self, clock: &VClock<A>) {
-        self.inner.forget(&clock);
+impl<A: Actor> DeltaCmRDT<A> for GCounter<A> {
+    type Delta = Self;
+
+    /// The delta holds the counters of every actor that has advanced past `since`.
+    fn delta(&self, since: &VClock<A>) -> Self {
+        Self {
+            inner: self
+                .inner
+                .iter()
+                .filter(|dot| dot.counter > since.get(dot.actor))
+                .map(|dot| Dot::new(dot.actor.clone(), dot.counter))
+                .collect(),
+        }
+    }
+
+    fn merge_delta(&mut self, delta: Self) {
+        self.merge(delta);
     }
 }
 
+/// The counts in a `GCounter` are data rather than causal metadata, forgetting them
+/// would lose increments, so `forget` is a no-op.
+impl<A: Actor> Causal<A> for GCounter<A> {}
+
 impl<A: Actor> GCounter<A> {
     /// Produce a new `GCounter`.
     pub fn new() -> Self {
@@ -65,12 +93,39 @@ impl<A: Actor> GCounter<A> {
         self.inner.inc(actor)
     }
 
+    /// Merge another counter into this one, returning how much the total grew.
+    ///
+    /// This is named `merge_changed` to keep it apart from `DeltaCmRDT::merge_delta`.
+    pub fn merge_changed(&mut self, other: &Self) -> u64 {
+        let mut increase = 0;
+        for dot in other.inner.iter() {
+            let ours = self.inner.get(dot.actor);
+            if dot.counter > ours {
+                increase += dot.counter - ours;
+                self.inner.apply(Dot::new(dot.actor.clone(), dot.counter));
+            }
+        }
+        increase
+    }
+
     /// Return the current sum of this counter.
     pub fn read(&self) -> BigUint {
         self.inner.iter().map(|dot| dot.counter).sum()
     }
 }
 
+impl<A: Actor + Arbitrary> Arbitrary for GCounter<A> {
+    fn arbitrary<G: Gen>(g: &mut G) -> Self {
+        Self {
+            inner: VClock::arbitrary(g),
+        }
+    }
+
+    fn shrink(&self) -> Box<dyn Iterator<Item = Self>> {
+        Box::new(self.inner.shrink().map(|inner| Self { inner }))
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -89,4 +144,66 @@ mod test {
 
         assert_eq!(a.read(), b.read() + BigUint::from(1u8));
     }
+
+    #[test]
+    fn test_forget_is_a_noop() {
+        let mut a = GCounter::new();
+        a.apply(a.inc("A"));
+        a.apply(a.inc("B"));
+        let before = a.clone();
+
+        a.forget(&a.inner.clone());
+        assert_eq!(a, before);
+
+        // while an overridden forget still compacts
+        let mut reg = crate::MVReg::new();
+        reg.apply(reg.write(1, reg.read_ctx().derive_add_ctx("A")));
+        reg.forget(&reg.read_ctx().add_clock);
+        assert!(reg.read().val.is_empty());
+    }
+
+    #[test]
+    fn test_op_id() {
+        let a = GCounter::new();
+        let op = a.inc("A");
+        assert_eq!(GCounter::op_id(&op), Dot::new("A", 1));
+    }
+
+    #[test]
+    fn test_merge_changed() {
+        let mut a = GCounter::new();
+        a.apply(a.inc("A"));
+
+        let mut peer = a.clone();
+        for _ in 0..4 {
+            peer.apply(peer.inc("A"));
+        }
+        for _ in 0..3 {
+            peer.apply(peer.inc("B"));
+        }
+
+        assert_eq!(a.merge_changed(&peer), 7);
+        assert_eq!(a, peer);
+        assert_eq!(a.merge_changed(&peer), 0);
+    }
+
+    #[test]
+    fn test_delta() {
+        let mut a = GCounter::new();
+        a.apply(a.inc("A"));
+        a.apply(a.inc("B"));
+
+        let mut peer = a.clone();
+
+        for _ in 0..3 {
+            a.apply(a.inc("A"));
+        }
+
+        let delta = a.delta(&peer.inner);
+        assert_eq!(delta.inner, Dot::new("A", 4).into());
+
+        peer.merge_delta(delta);
+        assert_eq!(peer.read(), BigUint::from(5u8));
+        assert_eq!(peer, a);
+    }
 }
@@ -1,5 +1,6 @@
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeSet;
+use std::convert::Infallible;
 
 use crate::{CmRDT, CvRDT};
 
@@ -42,6 +43,7 @@ impl<T: Ord + Clone> CvRDT for GSet<T> {
 
 impl<T: Ord> CmRDT for GSet<T> {
     type Op = T;
+    type Validation = Infallible;
 
     fn apply(&mut self, op: Self::Op) {
         self.insert(op);
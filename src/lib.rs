@@ -7,16 +7,27 @@
 //! operations.
 //!
 //! [crdt]: https://en.wikipedia.org/wiki/Conflict-free_replicated_data_type
+//!
+//! The `std` feature is on by default. Without it the crate is `no_std` and only
+//! needs `alloc`, providing the `VClock`, `MVReg` and `LSeq` core along with their
+//! traits and contexts.
 #![crate_type = "lib"]
 #![deny(missing_docs)]
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
 
 mod error;
 pub use crate::error::Error;
 
 mod traits;
-pub use crate::traits::{Actor, Causal, CmRDT, CvRDT, FunkyCmRDT, FunkyCvRDT};
+pub use crate::traits::{
+    Actor, Causal, CausalContext, CmRDT, Crdt, CvRDT, DeltaCmRDT, FunkyCmRDT, FunkyCvRDT, OpId,
+    Snapshot,
+};
 
 /// This module contains a Last-Write-Wins Register.
+#[cfg(feature = "std")]
 pub mod lwwreg;
 
 /// This module contains a Multi-Value Register.
@@ -29,18 +40,23 @@ pub mod vclock;
 pub mod dot;
 
 /// This module contains an Observed-Remove Set With Out Tombstones.
+#[cfg(feature = "std")]
 pub mod orswot;
 
 /// This module contains a Grow-only Counter.
+#[cfg(feature = "std")]
 pub mod gcounter;
 
 /// This module contains a Grow-only Set.
+#[cfg(feature = "std")]
 pub mod gset;
 
 /// This module contains a Positive-Negative Counter.
+#[cfg(feature = "std")]
 pub mod pncounter;
 
 /// This module contains a Map with Reset-Remove and Observed-Remove semantics.
+#[cfg(feature = "std")]
 pub mod map;
 
 /// This module contains context for editing a CRDT.
@@ -50,13 +66,26 @@ pub mod ctx;
 pub mod lseq;
 
 /// Version Vector with Exceptions
+#[cfg(feature = "std")]
 pub mod vvwe;
 
+/// Hashing for the `no_std` build, which has no `DefaultHasher` or thread rng
+#[cfg(not(feature = "std"))]
+mod fnv;
+
+/// Compile checks for the `no_std` build, see `cargo build --no-default-features`
+#[cfg(not(feature = "std"))]
+mod no_std_smoke;
+
 /// Top-level re-exports for CRDT structures.
+pub use crate::{dot::Dot, mvreg::MVReg, vclock::VClock};
+
+/// Top-level re-exports for CRDT structures that need `std`.
+#[cfg(feature = "std")]
 pub use crate::{
-    dot::Dot, gcounter::GCounter, gset::GSet, lwwreg::LWWReg, map::Map, mvreg::MVReg,
-    orswot::Orswot, pncounter::PNCounter, vclock::VClock,
+    gcounter::GCounter, gset::GSet, lwwreg::LWWReg, map::Map, orswot::Orswot, pncounter::PNCounter,
 };
 
 /// A re-export of the quickcheck crate for use in property based testing of user code
+#[cfg(feature = "std")]
 pub use quickcheck;
@@ -39,20 +39,33 @@
 /// Contains the implementation of the exponential tree for LSeq
 pub mod ident;
 
-use ident::{IdentGen, Identifier};
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cmp;
+use core::fmt::{self, Display};
+use ident::{AllocStrategy, IdentGen, Identifier, OrderedBytes};
 use serde::{Deserialize, Serialize};
+#[cfg(feature = "std")]
+use std::error;
 
-use crate::{Actor, CmRDT, Dot};
+use crate::dot::OrdDot;
+#[cfg(feature = "std")]
+use crate::quickcheck::{Arbitrary, Gen};
+use crate::{Actor, Causal, CmRDT, DeltaCmRDT, Dot, OpId, Snapshot, VClock};
 
 /// An `Entry` to the LSEQ consists of:
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash, PartialOrd)]
-pub struct Entry<T, A: Actor> {
+pub struct Entry<T, A: Actor, M = ()> {
     /// The identifier of the entry.
     pub id: Identifier<A>,
     /// The site id that inserted this entry.
     pub dot: Dot<A>,
     /// The element for the entry.
     pub val: T,
+    /// Metadata attached by the inserting site, e.g. the author and time of the insert.
+    #[serde(default)]
+    pub meta: M,
 }
 
 /// As described in the module documentation:
@@ -60,16 +73,76 @@ pub struct Entry<T, A: Actor> {
 /// An LSEQ tree is a CRDT for storing sequences of data (Strings, ordered lists).
 /// It provides an efficient view of the stored sequence, with fast index, insertion and deletion
 /// operations.
-#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Hash, PartialOrd)]
-pub struct LSeq<T, A: Actor> {
-    seq: Vec<Entry<T, A>>,
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash, PartialOrd)]
+#[serde(bound(
+    deserialize = "T: Deserialize<'de>, A: Deserialize<'de>, M: Deserialize<'de> + Default"
+))]
+pub struct LSeq<T, A: Actor, M = ()> {
+    seq: Vec<Entry<T, A, M>>,
     gen: IdentGen<A>,
     dot: Dot<A>,
+    /// The stamp of the winning move of every moved element, keyed by the element's dot
+    #[serde(default, with = "moves_serde")]
+    moves: BTreeMap<(A, u64), Dot<A>>,
+}
+
+/// A position in an LSeq that stays attached to its element as other elements are
+/// inserted and deleted, e.g. the caret of a text editor.
+///
+/// A cursor holds the identifier and dot of the element it was taken at, which are the
+/// same at every site, so it can be shipped to other sites and resolved against their
+/// replicas. The dot lets the cursor follow its element through moves.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Cursor<A: Actor> {
+    id: Identifier<A>,
+    dot: OrdDot<A>,
+}
+
+/// The plain data state of an LSeq, see `Snapshot`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(bound(
+    deserialize = "T: Deserialize<'de>, A: Deserialize<'de>, M: Deserialize<'de> + Default"
+))]
+pub struct State<T, A: Actor, M = ()> {
+    /// The live elements, in sequence order
+    pub entries: Vec<Entry<T, A, M>>,
+    /// The identifier allocator of the site, including its allocation strategy
+    pub gen: IdentGen<A>,
+    /// The dot of the last op issued by the site
+    pub dot: Dot<A>,
+    /// The stamp of the winning move of every moved element, keyed by the element's dot
+    #[serde(with = "moves_serde")]
+    pub moves: BTreeMap<(A, u64), Dot<A>>,
+}
+
+// Formats like JSON only allow string keys, so we (de)serialize the moves as a list of pairs
+mod moves_serde {
+    use alloc::collections::BTreeMap;
+    use alloc::vec::Vec;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    use crate::{Actor, Dot};
+
+    type Moves<A> = BTreeMap<(A, u64), Dot<A>>;
+
+    pub fn serialize<A: Actor + Serialize, S: Serializer>(
+        moves: &Moves<A>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(moves.iter())
+    }
+
+    pub fn deserialize<'de, A: Actor + Deserialize<'de>, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Moves<A>, D::Error> {
+        let pairs = Vec::<((A, u64), Dot<A>)>::deserialize(deserializer)?;
+        Ok(pairs.into_iter().collect())
+    }
 }
 
 /// Operations that can be performed on an LSeq tree
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash, PartialOrd)]
-pub enum Op<T, A: Actor> {
+pub enum Op<T, A: Actor, M = ()> {
     /// Insert an element
     Insert {
         /// Identifier to insert at
@@ -78,6 +151,9 @@ pub enum Op<T, A: Actor> {
         dot: Dot<A>,
         /// Element to insert
         val: T,
+        /// Metadata attached to the element
+        #[serde(default)]
+        meta: M,
     },
     /// Delete an element
     Delete {
@@ -88,50 +164,249 @@ pub enum Op<T, A: Actor> {
         /// id of site that issued delete
         dot: Dot<A>,
     },
+    /// Move an element to a new identifier, keeping its dot
+    Move {
+        /// The original clock information of the insertion we're moving
+        remote: Dot<A>,
+        /// Identifier of the element when the move was issued
+        id: Identifier<A>,
+        /// Identifier to move the element to
+        new_id: Identifier<A>,
+        /// id of site that issued move
+        dot: Dot<A>,
+        /// Orders moves of the same element, the greatest counter wins with ties broken by
+        /// actor. Its counter is one more than that of the last move the site saw for the
+        /// element, so a move always wins over the moves it has seen.
+        stamp: Dot<A>,
+    },
+    /// Insert a run of elements, equivalent to one `Insert` per element
+    InsertMany {
+        /// Identifiers to insert at, one per element
+        ids: Vec<Identifier<A>>,
+        /// clock of site that issued the first insertion, the i'th element was
+        /// inserted at `dot.counter + i`
+        dot: Dot<A>,
+        /// Elements to insert
+        vals: Vec<T>,
+        /// Metadata attached to every element
+        meta: M,
+    },
 }
 
-impl<T, A: Actor> Op<T, A> {
+/// Error returned by `LSeq::validate_op` and `LSeq::try_apply` for ops that `apply`
+/// would silently ignore
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OpError {
+    /// The op's dot has a zero counter, which no site ever issues
+    ZeroCounterDot,
+    /// The op deletes an element that is not in the sequence, either because it was
+    /// already deleted or because its insert hasn't been delivered yet
+    UnknownId,
+    /// The op inserts at an identifier already taken by an element inserted by a
+    /// different op. Identifiers embed the allocating site, so no site issues such an op.
+    DuplicateId,
+    /// The op is an `InsertMany` with no elements, or with a different number of
+    /// identifiers and elements
+    MalformedRun,
+}
+
+#[cfg(feature = "std")]
+impl error::Error for OpError {}
+
+impl Display for OpError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            OpError::ZeroCounterDot => write!(f, "op dot has a zero counter"),
+            OpError::UnknownId => write!(f, "deleted identifier is not in the sequence"),
+            OpError::DuplicateId => write!(f, "inserted identifier is taken by another element"),
+            OpError::MalformedRun => {
+                write!(
+                    f,
+                    "insert run is empty or its identifiers don't match its elements"
+                )
+            }
+        }
+    }
+}
+
+/// Renders the live elements in order. Elements are written back to back, which is
+/// what you want for text, use the alternate form (`{:#}`) to separate them with `, `.
+impl<T: Display, A: Actor, M> Display for LSeq<T, A, M> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let separator = if f.alternate() { ", " } else { "" };
+        for (i, entry) in self.seq.iter().enumerate() {
+            if i > 0 {
+                write!(f, "{}", separator)?;
+            }
+            write!(f, "{}", entry.val)?;
+        }
+        Ok(())
+    }
+}
+
+impl<T, A: Actor, M> Op<T, A, M> {
     /// Return the Dot originating the operation
     pub fn dot(&self) -> &Dot<A> {
         match self {
-            Op::Insert { dot, .. } | Op::Delete { dot, .. } => dot,
+            Op::Insert { dot, .. }
+            | Op::Delete { dot, .. }
+            | Op::Move { dot, .. }
+            | Op::InsertMany { dot, .. } => dot,
         }
     }
 
-    /// Return the Identifier contained in the operation
-    pub fn id(&self) -> &Identifier<A> {
+    /// Return the Identifier contained in the operation, the first one for `InsertMany`
+    /// and the new one for `Move`. Returns `None` for an `InsertMany` with no identifiers.
+    pub fn id(&self) -> Option<&Identifier<A>> {
         match self {
-            Op::Insert { id, .. } | Op::Delete { id, .. } => id,
+            Op::Insert { id, .. } | Op::Delete { id, .. } => Some(id),
+            Op::Move { new_id, .. } => Some(new_id),
+            Op::InsertMany { ids, .. } => ids.first(),
+        }
+    }
+}
+
+impl<T: Clone, A: Actor, M: Clone> Op<T, A, M> {
+    /// Squash a batch of ops into the minimal set of ops with the same net effect.
+    ///
+    /// Inserts that are deleted later in the batch are dropped along with their deletes
+    /// and moves.
+    /// An `InsertMany` with some of its elements deleted is split into one `Insert` per
+    /// remaining element. Deletes of elements inserted outside the batch are kept as is.
+    pub fn squash(ops: &[Self]) -> Vec<Self> {
+        let inserted: BTreeSet<OrdDot<A>> = ops
+            .iter()
+            .flat_map(|op| match op {
+                Op::Insert { dot, .. } => vec![OrdDot::from(dot.clone())],
+                Op::InsertMany { dot, vals, .. } => (0..vals.len())
+                    .map(|i| OrdDot::from(Dot::new(dot.actor.clone(), dot.counter + i as u64)))
+                    .collect(),
+                Op::Delete { .. } | Op::Move { .. } => vec![],
+            })
+            .collect();
+        let cancelled: BTreeSet<OrdDot<A>> = ops
+            .iter()
+            .filter_map(|op| match op {
+                Op::Delete { remote, .. } => Some(OrdDot::from(remote.clone())),
+                _ => None,
+            })
+            .filter(|remote| inserted.contains(remote))
+            .collect();
+        let is_cancelled = |dot: &Dot<A>| cancelled.contains(&OrdDot::from(dot.clone()));
+
+        let mut squashed = Vec::with_capacity(ops.len());
+        for op in ops {
+            match op {
+                Op::Insert { dot, .. }
+                | Op::Delete { remote: dot, .. }
+                | Op::Move { remote: dot, .. }
+                    if is_cancelled(dot) => {}
+                Op::InsertMany {
+                    ids,
+                    dot,
+                    vals,
+                    meta,
+                } => {
+                    let dots: Vec<_> = (0..vals.len())
+                        .map(|i| Dot::new(dot.actor.clone(), dot.counter + i as u64))
+                        .collect();
+                    if !dots.iter().any(is_cancelled) {
+                        squashed.push(op.clone());
+                        continue;
+                    }
+                    for ((id, dot), val) in ids.iter().zip(dots).zip(vals) {
+                        if !is_cancelled(&dot) {
+                            squashed.push(Op::Insert {
+                                id: id.clone(),
+                                dot,
+                                val: val.clone(),
+                                meta: meta.clone(),
+                            });
+                        }
+                    }
+                }
+                _ => squashed.push(op.clone()),
+            }
         }
+        squashed
     }
 }
 
 impl<T: Clone, A: Actor> LSeq<T, A> {
     /// Create an empty LSEQ
     pub fn new(id: A) -> Self {
+        LSeq::with_metadata(id)
+    }
+
+    /// Create an empty LSEQ with custom base size
+    pub fn new_with_args(id: A, base: u8, boundary: u64) -> Self {
         LSeq {
             seq: Vec::new(),
-            gen: IdentGen::new(id.clone()),
+            gen: IdentGen::new_with_args(id.clone(), base, boundary),
             dot: Dot::new(id, 0),
+            moves: BTreeMap::new(),
         }
     }
 
-    /// Create an empty LSEQ with custom base size
-    pub fn new_with_args(id: A, base: u8, boundary: u64) -> Self {
+    /// Create an empty LSEQ that allocates identifiers with the given strategy.
+    ///
+    /// `AllocStrategy::Alternating` keeps identifiers short under both runs of appends and
+    /// runs of prepends, while a fixed boundary strategy only handles one of the two.
+    pub fn with_strategy(id: A, strategy: AllocStrategy) -> Self {
         LSeq {
             seq: Vec::new(),
-            gen: IdentGen::new_with_args(id.clone(), base, boundary),
+            gen: IdentGen::with_strategy(id.clone(), strategy),
+            dot: Dot::new(id, 0),
+            moves: BTreeMap::new(),
+        }
+    }
+}
+
+impl<T: Clone, A: Actor, M: Clone> LSeq<T, A, M> {
+    /// Create an empty LSEQ whose elements carry metadata of type `M`
+    pub fn with_metadata(id: A) -> Self {
+        LSeq {
+            seq: Vec::new(),
+            gen: IdentGen::new(id.clone()),
             dot: Dot::new(id, 0),
+            moves: BTreeMap::new(),
         }
     }
 
-    /// Perform a local insertion of an element at a given position.
+    /// Perform a local insertion of an element at a given position, with default metadata.
     /// If `ix` is greater than the length of the LSeq then it is appended to the end.
     ///
     /// # Panics
     ///
     /// * If the allocation of a new index was not between `ix` and `ix - 1`.
-    pub fn insert_index(&mut self, ix: usize, val: T) -> Op<T, A> {
+    pub fn insert_index(&mut self, ix: usize, val: T) -> Op<T, A, M>
+    where
+        M: Default,
+    {
+        self.insert_index_with_meta(ix, val, M::default())
+    }
+
+    /// Perform a local insertion of an element at a given position, attaching `meta` to it.
+    ///
+    /// The metadata travels with the insert and is never used to order elements, so it
+    /// has no effect on convergence.
+    pub fn insert_index_with_meta(&mut self, ix: usize, val: T, meta: M) -> Op<T, A, M> {
+        let ix_ident = self.alloc_at(ix);
+
+        self.dot.apply_inc();
+        let op = Op::Insert {
+            id: ix_ident,
+            dot: self.dot.clone(),
+            val,
+            meta,
+        };
+        // TODO: refactor to follow the library API (don't apply ops immediately)
+        self.apply(op.clone());
+        op
+    }
+
+    /// Allocate an identifier that places an element at position `ix`
+    fn alloc_at(&mut self, ix: usize) -> Identifier<A> {
         let min_id = self.gen.lower();
         let max_id = self.gen.upper();
 
@@ -152,33 +427,83 @@ impl<T: Clone, A: Actor> LSeq<T, A> {
             (prev, next)
         };
 
-        let ix_ident = self.gen.alloc(&lower_id, &upper_id);
+        let ix_ident = self.gen.alloc(lower_id, upper_id);
 
         assert!(lower_id < &ix_ident);
         assert!(&ix_ident < upper_id);
+        ix_ident
+    }
 
-        self.dot.apply_inc();
-        let op = Op::Insert {
-            id: ix_ident,
-            dot: self.dot.clone(),
-            val,
-        };
-        // TODO: refactor to follow the library API (don't apply ops immediately)
-        self.apply(op.clone());
-        op
+    /// Perform a local insertion of a run of elements starting at `ix`.
+    ///
+    /// The elements are inserted one after the other as with `insert_index`, but they are
+    /// shipped as a single `InsertMany` op. Returns `None` if `vals` is empty.
+    pub fn insert_many(&mut self, ix: usize, vals: Vec<T>) -> Option<Op<T, A, M>>
+    where
+        M: Default,
+    {
+        let mut ids = Vec::with_capacity(vals.len());
+        let mut first_dot = None;
+        for (i, val) in vals.iter().cloned().enumerate() {
+            if let Op::Insert { id, dot, .. } = self.insert_index(ix + i, val) {
+                ids.push(id);
+                first_dot.get_or_insert(dot);
+            }
+        }
+
+        first_dot.map(|dot| Op::InsertMany {
+            ids,
+            dot,
+            vals,
+            meta: M::default(),
+        })
+    }
+
+    /// Perform a local insertion of the values of `iter` one after the other, starting
+    /// at `ix`.
+    ///
+    /// Unlike `insert_many`, each value is shipped as its own `Insert` op, so the ops can
+    /// be replicated to sites that don't understand `InsertMany`.
+    pub fn extend_from(&mut self, ix: usize, iter: impl IntoIterator<Item = T>) -> Vec<Op<T, A, M>>
+    where
+        M: Default,
+    {
+        iter.into_iter()
+            .enumerate()
+            .map(|(i, val)| self.insert_index(ix + i, val))
+            .collect()
     }
 
     /// Perform a local insertion of an element at the end of the sequence.
-    pub fn append(&mut self, c: T) -> Op<T, A> {
+    pub fn append(&mut self, c: T) -> Op<T, A, M>
+    where
+        M: Default,
+    {
         let ix = self.seq.len();
         self.insert_index(ix, c)
     }
 
+    /// Perform a local insertion of an element at its sorted position.
+    ///
+    /// The position is found through a binary search over the sequence, so this only keeps the
+    /// sequence sorted if it was already sorted. Concurrent sorted inserts from different sites
+    /// may leave the sequence transiently out of order.
+    pub fn insert_sorted(&mut self, val: T) -> Op<T, A, M>
+    where
+        T: Ord,
+        M: Default,
+    {
+        let ix = match self.seq.binary_search_by(|entry| entry.val.cmp(&val)) {
+            Ok(ix) | Err(ix) => ix,
+        };
+        self.insert_index(ix, val)
+    }
+
     /// Perform a local deletion at `ix`.
     ///
     /// If `ix` is out of bounds, i.e. `ix > self.len()`, then
     /// the `Op` is not performed and `None` is returned.
-    pub fn delete_index(&mut self, ix: usize) -> Option<Op<T, A>> {
+    pub fn delete_index(&mut self, ix: usize) -> Option<Op<T, A, M>> {
         if ix >= self.seq.len() {
             return None;
         }
@@ -199,7 +524,7 @@ impl<T: Clone, A: Actor> LSeq<T, A> {
 
     /// Perform a local deletion at `ix`. If `ix` is out of bounds
     /// then the last element will be deleted, i.e. `self.len() - 1`.
-    pub fn delete_index_or_last(&mut self, ix: usize) -> Op<T, A> {
+    pub fn delete_index_or_last(&mut self, ix: usize) -> Op<T, A, M> {
         match self.delete_index(ix) {
             None => self
                 .delete_index(self.len() - 1)
@@ -208,6 +533,165 @@ impl<T: Clone, A: Actor> LSeq<T, A> {
         }
     }
 
+    /// Perform a local reversal of the sequence.
+    ///
+    /// The reversal is made of moves, so every element keeps its dot: an element deleted
+    /// concurrently stays deleted, and concurrent moves of the same element don't duplicate
+    /// it. The returned ops must all be shipped to other sites. Edits made concurrently with
+    /// a reverse will converge, but they are not reordered by it.
+    pub fn reverse(&mut self) -> Vec<Op<T, A, M>> {
+        let last = match self.seq.len().checked_sub(1) {
+            Some(last) => last,
+            None => return Vec::new(),
+        };
+        // bring the last of the not yet reversed elements forward, one position at a time
+        (0..last)
+            .filter_map(|ix| self.move_item(last, ix))
+            .collect()
+    }
+
+    /// Perform a local rebalance of the sequence.
+    ///
+    /// Every element is moved, in order, to a freshly allocated identifier, which compacts
+    /// identifiers that have grown deep. The rebalance is made of moves, so elements keep
+    /// their dots and an element deleted concurrently stays deleted. Elements inserted
+    /// concurrently against the old identifiers are kept, but their position relative to
+    /// the rebalanced elements is not preserved.
+    pub fn rebalance(&mut self) -> Vec<Op<T, A, M>> {
+        let max_id = self.gen.upper();
+        let old_ids: Vec<Identifier<A>> = self.seq.iter().map(|entry| entry.id.clone()).collect();
+
+        let mut ops = Vec::with_capacity(old_ids.len());
+        let mut prev = self.gen.lower();
+        for old_id in old_ids {
+            // earlier elements only ever move to fresh identifiers, so this one hasn't moved
+            let ix = self
+                .position_of(&old_id)
+                .expect("rebalance: element moved before its turn");
+            // allocate as if appending, below any identifier still held by another element
+            let mut upper = max_id.clone();
+            let new_id = loop {
+                let id = self.gen.alloc(&prev, &upper);
+                match self.position_of(&id) {
+                    Some(taken) if taken != ix => upper = id,
+                    _ => break id,
+                }
+            };
+            if new_id != old_id {
+                ops.push(self.move_entry(ix, new_id.clone()));
+            }
+            prev = new_id;
+        }
+        ops
+    }
+
+    /// Perform a local swap of the elements at `i` and `j`.
+    ///
+    /// The swap is made of two moves, so both elements keep their dots and concurrent swaps
+    /// or moves of the same elements never duplicate them: each element ends up where the
+    /// move with the greatest stamp put it. If either index is out of bounds, or they are
+    /// equal, nothing is done and no ops are returned.
+    pub fn swap(&mut self, i: usize, j: usize) -> Vec<Op<T, A, M>> {
+        if i == j || i >= self.seq.len() || j >= self.seq.len() {
+            return Vec::new();
+        }
+        let (i, j) = (cmp::min(i, j), cmp::max(i, j));
+
+        // moving `i` to `j` shifts the element at `j` down to `j - 1`, which then moves to `i`.
+        // For neighbors the first move already swaps them and the second is a no-op.
+        let mut ops = Vec::with_capacity(2);
+        ops.extend(self.move_item(i, j));
+        ops.extend(self.move_item(j - 1, i));
+        ops
+    }
+
+    /// Perform a local move of the element at `from` so that it ends up at index `to`.
+    ///
+    /// The element keeps its dot and is given a fresh identifier, so unlike a delete and
+    /// re-insert, concurrent moves of the same element don't duplicate it: every site
+    /// keeps the element where the move with the greatest stamp put it. A move concurrent
+    /// with a delete of the element has no effect. Returns `None` if `from` is out of
+    /// bounds or the element is already at `to`, indices past the end move it to the end.
+    pub fn move_item(&mut self, from: usize, to: usize) -> Option<Op<T, A, M>> {
+        if from >= self.seq.len() {
+            return None;
+        }
+        let to = cmp::min(to, self.seq.len() - 1);
+        if from == to {
+            return None;
+        }
+
+        // allocate between the neighbors the element will have once it's taken out
+        let entry = self.seq.remove(from);
+        let new_id = self.alloc_at(to);
+        self.seq.insert(from, entry);
+        Some(self.move_entry(from, new_id))
+    }
+
+    /// Move the element at `ix` to `new_id`, stamping the move so it wins over the moves
+    /// of the element this site has seen
+    fn move_entry(&mut self, ix: usize, new_id: Identifier<A>) -> Op<T, A, M> {
+        let id = self.seq[ix].id.clone();
+        let remote = self.seq[ix].dot.clone();
+
+        let last_stamp = self.moves.get(&(remote.actor.clone(), remote.counter));
+        let stamp = Dot::new(self.actor(), last_stamp.map_or(0, |s| s.counter) + 1);
+        self.dot.apply_inc();
+        let op = Op::Move {
+            remote,
+            id,
+            new_id,
+            dot: self.dot.clone(),
+            stamp,
+        };
+        self.apply(op.clone());
+        op
+    }
+
+    /// Perform a local deletion of the elements at indices `start..end`.
+    ///
+    /// Bounds past the end are clamped to the length of the sequence. One delete op is
+    /// returned per deleted element, and they must all be shipped to other sites.
+    pub fn delete_range(&mut self, start: usize, end: usize) -> Vec<Op<T, A, M>> {
+        let end = cmp::min(end, self.seq.len());
+        let start = cmp::min(start, end);
+        (start..end)
+            .filter_map(|_| self.delete_index(start))
+            .collect()
+    }
+
+    /// Perform a local deletion of every element equal to `val`. The returned deletes must
+    /// all be shipped to other sites.
+    pub fn delete_value(&mut self, val: &T) -> Vec<Op<T, A, M>>
+    where
+        T: PartialEq,
+    {
+        self.find_all(|v| v == val)
+            .into_iter()
+            .rev()
+            .filter_map(|ix| self.delete_index(ix))
+            .collect()
+    }
+
+    /// Perform a local deletion of every element equal to the element before it, so that
+    /// no two consecutive elements are equal. The returned deletes must all be shipped to
+    /// other sites.
+    pub fn dedup(&mut self) -> Vec<Op<T, A, M>>
+    where
+        T: PartialEq,
+    {
+        let duplicates: Vec<usize> = (1..self.seq.len())
+            .filter(|&ix| self.seq[ix].val == self.seq[ix - 1].val)
+            .collect();
+
+        // delete from the back so that the remaining indices still point at the duplicates
+        duplicates
+            .into_iter()
+            .rev()
+            .filter_map(|ix| self.delete_index(ix))
+            .collect()
+    }
+
     /// Get the length of the LSEQ.
     pub fn len(&self) -> usize {
         self.seq.len()
@@ -223,51 +707,567 @@ impl<T: Clone, A: Actor> LSeq<T, A> {
         self.seq.iter().map(|Entry { val, .. }| val)
     }
 
+    /// Get the elements from index `start` to the end of the sequence.
+    /// A `start` past the end yields nothing.
+    pub fn iter_from(&self, start: usize) -> impl Iterator<Item = &T> + '_ {
+        self.iter_range(start, self.seq.len())
+    }
+
+    /// Get the elements at indices `start..end`, without materializing the whole sequence.
+    /// Bounds past the end are clamped to the length of the sequence.
+    pub fn iter_range(&self, start: usize, end: usize) -> impl Iterator<Item = &T> + '_ {
+        let end = cmp::min(end, self.seq.len());
+        let start = cmp::min(start, end);
+        self.seq[start..end].iter().map(|Entry { val, .. }| val)
+    }
+
     /// Get an element at an index from the sequence represented by the LSEQ.
     pub fn get(&self, ix: usize) -> Option<&T> {
         self.seq.get(ix).map(|Entry { val, .. }| val)
     }
 
+    /// Get the metadata attached to the element at `ix`, e.g. the author of the insert.
+    pub fn author_at(&self, ix: usize) -> Option<&M> {
+        self.seq.get(ix).map(|Entry { meta, .. }| meta)
+    }
+
+    /// Get the elements an insertion at `ix` would land between, i.e. the elements at
+    /// `ix - 1` and `ix`.
+    pub fn neighbors(&self, ix: usize) -> (Option<&T>, Option<&T>) {
+        let before = ix.checked_sub(1).and_then(|i| self.get(i));
+        (before, self.get(ix))
+    }
+
+    /// Compare two positions in the sequence by their identifiers, without looking them up.
+    ///
+    /// Identifiers are totally ordered, so this also orders positions whose elements
+    /// have been deleted or not yet delivered to this site.
+    pub fn cmp_positions(&self, a: &Identifier<A>, b: &Identifier<A>) -> cmp::Ordering {
+        a.cmp(b)
+    }
+
+    /// Get the current index of the element inserted at `id` by `dot`, e.g. a cursor
+    /// captured from an insert op. The element is followed through moves, including those
+    /// made by `swap`, `rebalance` and `reverse`. Returns `None` once that element is gone.
+    ///
+    /// The dot is checked as well as the identifier since a re-insert may reuse the
+    /// identifier of a deleted element for a different value.
+    pub fn index_of(&self, id: &Identifier<A>, dot: &Dot<A>) -> Option<usize> {
+        self.find(id, dot)
+    }
+
+    /// Get a cursor pointing at the element at `ix`, or `None` if `ix` is out of bounds.
+    pub fn cursor(&self, ix: usize) -> Option<Cursor<A>> {
+        self.seq.get(ix).map(|entry| Cursor {
+            id: entry.id.clone(),
+            dot: OrdDot::from(entry.dot.clone()),
+        })
+    }
+
+    /// Get the current index of the element a cursor points at, following it through
+    /// moves like `index_of`.
+    ///
+    /// If that element has been deleted, the cursor resolves to the element that now
+    /// follows the position it was taken at, or to the last element if it was at the end.
+    /// Returns `None` only if the sequence is empty.
+    pub fn resolve_cursor(&self, cursor: &Cursor<A>) -> Option<usize> {
+        if let Some(ix) = self.find(&cursor.id, &Dot::from(cursor.dot.clone())) {
+            return Some(ix);
+        }
+        match self.seq.binary_search_by(|e| e.id.cmp(&cursor.id)) {
+            Ok(ix) => Some(ix),
+            Err(ix) if ix < self.seq.len() => Some(ix),
+            Err(_) => self.seq.len().checked_sub(1),
+        }
+    }
+
+    /// Get the current index of the element at identifier `id`, or `None` if no element
+    /// is at that identifier, e.g. because it was deleted.
+    pub fn position_of(&self, id: &Identifier<A>) -> Option<usize> {
+        self.seq.binary_search_by(|e| e.id.cmp(id)).ok()
+    }
+
+    /// Get a key for the element at `ix` whose byte order matches the sequence order.
+    ///
+    /// The key is derived from the element's identifier, so it is the same at every site
+    /// and doesn't change as other elements are inserted or deleted. It can be stored
+    /// alongside the element to re-sort elements externally, e.g. in a database.
+    pub fn order_key(&self, ix: usize) -> Option<Vec<u8>>
+    where
+        A: OrderedBytes,
+    {
+        self.seq.get(ix).map(|Entry { id, .. }| id.order_key())
+    }
+
+    /// Get a mutable reference to the element at `ix`, for local-only edits.
+    ///
+    /// Mutating an element through this reference produces no op, so the change is
+    /// not replicated and sites will not converge on it.
+    pub fn value_mut(&mut self, ix: usize) -> Option<&mut T> {
+        self.seq.get_mut(ix).map(|Entry { val, .. }| val)
+    }
+
+    /// Get the indices of all elements that match `pred`, in sequence order.
+    pub fn find_all(&self, pred: impl Fn(&T) -> bool) -> Vec<usize> {
+        self.iter()
+            .enumerate()
+            .filter(|(_, val)| pred(val))
+            .map(|(ix, _)| ix)
+            .collect()
+    }
+
+    /// Check if the sequence represented by the LSEQ contains a value.
+    pub fn contains(&self, val: &T) -> bool
+    where
+        T: PartialEq,
+    {
+        self.iter().any(|v| v == val)
+    }
+
+    /// Get the fractional position of the element at `ix` within the sequence, in `[0, 1)`.
+    ///
+    /// Indices past the end are clamped to the last element, and an empty sequence maps
+    /// everything to `0.0`.
+    pub fn relative_position(&self, ix: usize) -> f64 {
+        if self.seq.is_empty() {
+            0.0
+        } else {
+            ix.min(self.seq.len() - 1) as f64 / self.seq.len() as f64
+        }
+    }
+
     /// Get last element of the sequence represented by the LSEQ.
     pub fn last(&self) -> Option<&T> {
         self.seq.last().map(|Entry { val, .. }| val)
     }
 
+    /// The depth of the deepest identifier currently in the LSEQ.
+    ///
+    /// Deep identifiers are more expensive to store and compare, so this can be monitored
+    /// to decide when the identifiers have grown too large.
+    pub fn max_identifier_depth(&self) -> usize {
+        self.seq
+            .iter()
+            .map(|entry| entry.id.depth())
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Build the ops a site that has seen the ops covered by `remote_clock` is missing
+    /// to reach this sequence, in sequence order.
+    ///
+    /// LSeq keeps no tombstones, so only inserts can be rebuilt: an element is shipped
+    /// at its current identifier if its dot isn't covered by `remote_clock`, and
+    /// elements inserted and deleted since are left out altogether. Deletes and moves
+    /// of elements the remote site has already seen must still be shipped as ops, as
+    /// with `DeltaCmRDT::delta`.
+    pub fn diff(&self, remote_clock: &VClock<A>) -> Vec<Op<T, A, M>> {
+        self.delta(remote_clock)
+            .into_iter()
+            .map(|Entry { id, dot, val, meta }| Op::Insert { id, dot, val, meta })
+            .collect()
+    }
+
     /// Actor who is initiating operations on this LSeq
     pub fn actor(&self) -> A {
         self.dot.actor.clone()
     }
 
     /// Insert an identifier and value in the LSEQ
-    fn insert(&mut self, ix: Identifier<A>, dot: Dot<A>, val: T) {
+    fn insert(&mut self, ix: Identifier<A>, dot: Dot<A>, val: T, meta: M) {
+        // a redelivered insert of an element that has moved since
+        if self.moves.contains_key(&(dot.actor.clone(), dot.counter)) {
+            return;
+        }
         // Inserts only have an impact if the identifier is not in the tree
         if let Err(res) = self.seq.binary_search_by(|e| e.id.cmp(&ix)) {
-            self.seq.insert(res, Entry { id: ix, dot, val });
+            let entry = Entry {
+                id: ix,
+                dot,
+                val,
+                meta,
+            };
+            self.seq.insert(res, entry);
         }
     }
 
+    /// Apply an op, reporting malformed ops instead of ignoring them like `apply` does.
+    ///
+    /// The op is only applied if `validate_op` accepts it. Note that deleting an unknown
+    /// identifier is expected when ops are redelivered, or delivered out of causal order.
+    pub fn try_apply(&mut self, op: Op<T, A, M>) -> Result<(), OpError> {
+        self.validate_op(&op)?;
+        self.apply(op);
+        Ok(())
+    }
+
     /// Remove an identifier from the LSEQ
-    fn delete(&mut self, ix: Identifier<A>) {
-        // Deletes only have an effect if the identifier is already in the tree
-        if let Ok(i) = self.seq.binary_search_by(|e| e.id.cmp(&ix)) {
+    fn delete(&mut self, ix: Identifier<A>, remote: Dot<A>) {
+        // Deletes only have an effect if the element they target is still in the tree,
+        // an element re-inserted at the same identifier by a later op is left alone
+        if let Some(i) = self.find(&ix, &remote) {
             self.seq.remove(i);
+            self.moves.remove(&(remote.actor, remote.counter));
         }
     }
+
+    /// Move the element inserted by `remote` to `new_id`, if `stamp` wins over the
+    /// element's previous moves
+    fn relocate(
+        &mut self,
+        ix: Identifier<A>,
+        remote: Dot<A>,
+        new_id: Identifier<A>,
+        stamp: Dot<A>,
+    ) {
+        let key = (remote.actor.clone(), remote.counter);
+        let wins = match self.moves.get(&key) {
+            Some(last) => (last.counter, &last.actor) < (stamp.counter, &stamp.actor),
+            None => true,
+        };
+        let from = match self.find(&ix, &remote) {
+            Some(from) if wins => from,
+            _ => return,
+        };
+        let mut entry = self.seq.remove(from);
+        match self.seq.binary_search_by(|e| e.id.cmp(&new_id)) {
+            Err(to) => {
+                entry.id = new_id;
+                self.seq.insert(to, entry);
+                self.moves.insert(key, stamp);
+            }
+            // the new identifier is taken by another element, see `validate_op`
+            Ok(_) => self.seq.insert(from, entry),
+        }
+    }
+
+    /// Find the element inserted by `dot`, which is at identifier `id` unless it has moved
+    fn find(&self, id: &Identifier<A>, dot: &Dot<A>) -> Option<usize> {
+        let at_id = self.position_of(id).filter(|&ix| &self.seq[ix].dot == dot);
+        at_id.or_else(|| {
+            if self.moves.contains_key(&(dot.actor.clone(), dot.counter)) {
+                self.seq.iter().position(|e| &e.dot == dot)
+            } else {
+                None
+            }
+        })
+    }
 }
 
-impl<T: Clone, A: Actor> CmRDT for LSeq<T, A> {
-    type Op = Op<T, A>;
+impl<T: Clone, A: Actor, M: Clone> CmRDT for LSeq<T, A, M> {
+    type Op = Op<T, A, M>;
+    type Validation = OpError;
+
+    fn validate_op(&self, op: &Self::Op) -> Result<(), OpError> {
+        Pending::new(self).validate(op)
+    }
+
     /// Apply an operation to an LSeq instance.
     ///
     /// If the operation is an insert and the identifier is **already** present in the LSEQ instance
     /// the result is a no-op
     ///
-    /// If the operation is a delete and the identifier is **not** present in the LSEQ instance the
-    /// result is a no-op
+    /// If the operation is a delete and the identifier is **not** present in the LSEQ instance, or
+    /// holds an element inserted by a different op than the one the delete targets, the result
+    /// is a no-op
+    ///
+    /// A delete whose dot has a zero counter was never issued by a site (counters start at 1), so
+    /// it is treated as malformed and ignored. So is an `InsertMany` whose identifiers and
+    /// elements don't pair up one to one.
     fn apply(&mut self, op: Self::Op) {
         match op {
-            Op::Insert { id, dot, val } => self.insert(id, dot, val),
-            Op::Delete { id, .. } => self.delete(id),
+            Op::Insert { id, dot, val, meta } => self.insert(id, dot, val, meta),
+            Op::InsertMany { ids, vals, .. } if ids.len() != vals.len() => (),
+            Op::InsertMany {
+                ids,
+                dot,
+                vals,
+                meta,
+            } => {
+                for (i, (id, val)) in ids.into_iter().zip(vals).enumerate() {
+                    let dot = Dot::new(dot.actor.clone(), dot.counter + i as u64);
+                    self.insert(id, dot, val, meta.clone());
+                }
+            }
+            Op::Delete { dot, .. } | Op::Move { dot, .. } if dot.counter == 0 => (),
+            Op::Delete { id, remote, .. } => self.delete(id, remote),
+            Op::Move {
+                remote,
+                id,
+                new_id,
+                stamp,
+                ..
+            } => self.relocate(id, remote, new_id, stamp),
+        }
+    }
+
+    /// The batch is checked up front against the sequence as the ops before each one leave
+    /// it, tracked as changes on top of the sequence, so it is validated without staging it
+    /// on a clone.
+    fn apply_all(&mut self, ops: &[Self::Op]) -> Result<(), (usize, OpError)>
+    where
+        Self: Clone,
+        Self::Op: Clone,
+    {
+        let mut pending = Pending::new(self);
+        for (i, op) in ops.iter().enumerate() {
+            pending.validate(op).map_err(|err| (i, err))?;
+            pending.record(op);
+        }
+        for op in ops {
+            self.apply(op.clone());
+        }
+        Ok(())
+    }
+}
+
+/// An LSeq as it will be once some ops are applied, kept as the changes the ops make on
+/// top of the sequence so a batch of ops can be validated without cloning the sequence
+struct Pending<'a, T, A: Actor, M> {
+    lseq: &'a LSeq<T, A, M>,
+    /// identifiers the ops filled, with the dot of their element, or emptied
+    ids: BTreeMap<Identifier<A>, Option<Dot<A>>>,
+    /// identifiers of the elements the ops inserted or moved, `None` for deleted ones
+    elems: BTreeMap<OrdDot<A>, Option<Identifier<A>>>,
+    /// stamps of the winning moves of the elements the ops moved or deleted
+    stamps: BTreeMap<OrdDot<A>, Option<Dot<A>>>,
+}
+
+impl<'a, T: Clone, A: Actor, M: Clone> Pending<'a, T, A, M> {
+    fn new(lseq: &'a LSeq<T, A, M>) -> Self {
+        Pending {
+            lseq,
+            ids: BTreeMap::new(),
+            elems: BTreeMap::new(),
+            stamps: BTreeMap::new(),
+        }
+    }
+
+    /// The dot of the element at `id`
+    fn holder(&self, id: &Identifier<A>) -> Option<Dot<A>> {
+        match self.ids.get(id) {
+            Some(dot) => dot.clone(),
+            None => self
+                .lseq
+                .position_of(id)
+                .map(|ix| self.lseq.seq[ix].dot.clone()),
+        }
+    }
+
+    /// The identifier the element inserted by `dot` is at, see `LSeq::find`
+    fn locate(&self, id: &Identifier<A>, dot: &Dot<A>) -> Option<Identifier<A>> {
+        match self.elems.get(&OrdDot::from(dot.clone())) {
+            Some(at) => at.clone(),
+            None => self
+                .lseq
+                .find(id, dot)
+                .map(|ix| self.lseq.seq[ix].id.clone()),
+        }
+    }
+
+    fn last_stamp(&self, dot: &Dot<A>) -> Option<Dot<A>> {
+        match self.stamps.get(&OrdDot::from(dot.clone())) {
+            Some(stamp) => stamp.clone(),
+            None => self
+                .lseq
+                .moves
+                .get(&(dot.actor.clone(), dot.counter))
+                .cloned(),
+        }
+    }
+
+    fn taken_by_other(&self, id: &Identifier<A>, dot: &Dot<A>) -> bool {
+        matches!(self.holder(id), Some(holder) if &holder != dot)
+    }
+
+    fn validate(&self, op: &Op<T, A, M>) -> Result<(), OpError> {
+        if op.dot().counter == 0 {
+            return Err(OpError::ZeroCounterDot);
+        }
+        match op {
+            Op::Insert { id, dot, .. } if self.taken_by_other(id, dot) => Err(OpError::DuplicateId),
+            Op::InsertMany { ids, vals, .. } if ids.is_empty() || ids.len() != vals.len() => {
+                Err(OpError::MalformedRun)
+            }
+            Op::InsertMany { ids, dot, .. }
+                if ids.iter().enumerate().any(|(i, id)| {
+                    self.taken_by_other(id, &Dot::new(dot.actor.clone(), dot.counter + i as u64))
+                }) =>
+            {
+                Err(OpError::DuplicateId)
+            }
+            Op::Delete { id, remote, .. } | Op::Move { id, remote, .. }
+                if self.locate(id, remote).is_none() =>
+            {
+                Err(OpError::UnknownId)
+            }
+            Op::Move { new_id, remote, .. } if self.taken_by_other(new_id, remote) => {
+                Err(OpError::DuplicateId)
+            }
+            _ => Ok(()),
         }
     }
+
+    /// Record the changes applying `op` makes, mirroring `LSeq::apply`
+    fn record(&mut self, op: &Op<T, A, M>) {
+        match op {
+            Op::Insert { id, dot, .. } => self.insert(id, dot),
+            Op::InsertMany { ids, vals, .. } if ids.len() != vals.len() => (),
+            Op::InsertMany { ids, dot, .. } => {
+                for (i, id) in ids.iter().enumerate() {
+                    self.insert(id, &Dot::new(dot.actor.clone(), dot.counter + i as u64));
+                }
+            }
+            Op::Delete { dot, .. } | Op::Move { dot, .. } if dot.counter == 0 => (),
+            Op::Delete { id, remote, .. } => {
+                if let Some(at) = self.locate(id, remote) {
+                    self.ids.insert(at, None);
+                    self.elems.insert(OrdDot::from(remote.clone()), None);
+                    self.stamps.insert(OrdDot::from(remote.clone()), None);
+                }
+            }
+            Op::Move {
+                remote,
+                id,
+                new_id,
+                stamp,
+                ..
+            } => {
+                let wins = match self.last_stamp(remote) {
+                    Some(last) => (last.counter, &last.actor) < (stamp.counter, &stamp.actor),
+                    None => true,
+                };
+                let from = match self.locate(id, remote) {
+                    Some(from) if wins && !self.taken_by_other(new_id, remote) => from,
+                    _ => return,
+                };
+                self.ids.insert(from, None);
+                self.ids.insert(new_id.clone(), Some(remote.clone()));
+                self.elems
+                    .insert(OrdDot::from(remote.clone()), Some(new_id.clone()));
+                self.stamps
+                    .insert(OrdDot::from(remote.clone()), Some(stamp.clone()));
+            }
+        }
+    }
+
+    /// Mirrors `LSeq::insert`
+    fn insert(&mut self, id: &Identifier<A>, dot: &Dot<A>) {
+        if self.last_stamp(dot).is_some() || self.holder(id).is_some() {
+            return;
+        }
+        self.ids.insert(id.clone(), Some(dot.clone()));
+        self.elems
+            .insert(OrdDot::from(dot.clone()), Some(id.clone()));
+    }
+}
+
+/// Appends each value to the end of the sequence as a local insertion.
+///
+/// The generated ops are applied locally and then dropped, so use `append` instead
+/// if the inserts need to be replicated to other sites.
+impl<T: Clone, A: Actor, M: Clone + Default> Extend<T> for LSeq<T, A, M> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for val in iter {
+            self.append(val);
+        }
+    }
+}
+
+impl<T: Clone, A: Actor, M: Clone> Snapshot for LSeq<T, A, M> {
+    type State = State<T, A, M>;
+
+    fn snapshot(&self) -> State<T, A, M> {
+        State {
+            entries: self.seq.clone(),
+            gen: self.gen.clone(),
+            dot: self.dot.clone(),
+            moves: self.moves.clone(),
+        }
+    }
+
+    fn restore(state: State<T, A, M>) -> Self {
+        LSeq {
+            seq: state.entries,
+            gen: state.gen,
+            dot: state.dot,
+            moves: state.moves,
+        }
+    }
+}
+
+/// Builds a local LSeq for the default actor, with the values inserted in order.
+///
+/// Every site seeded this way allocates its own identifiers, so to share a starting
+/// sequence between sites, seed one and replicate its ops, e.g. from `extend_from`.
+impl<T: Clone, A: Actor + Default> core::iter::FromIterator<T> for LSeq<T, A> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut seq = LSeq::new(A::default());
+        seq.extend(iter);
+        seq
+    }
+}
+
+/// LSeq keeps no tombstones, deleted elements are dropped as soon as the delete is
+/// applied, so there is no causal history to forget and `forget` is a no-op. The dots
+/// of live elements identify them and are kept.
+impl<T, A: Actor, M> Causal<A> for LSeq<T, A, M> {}
+
+/// LSeq keeps no tombstones, so a delta only carries inserts: deletes and moves must
+/// still be replicated as ops, and applied before the delta since an insert may reuse
+/// the identifier of a deleted element.
+impl<T: Clone, A: Actor, M: Clone> DeltaCmRDT<A> for LSeq<T, A, M> {
+    type Delta = Vec<Entry<T, A, M>>;
+
+    /// The delta holds the live entries whose dots are not covered by `since`.
+    fn delta(&self, since: &VClock<A>) -> Self::Delta {
+        self.seq
+            .iter()
+            .filter(|entry| entry.dot.counter > since.get(&entry.dot.actor))
+            .cloned()
+            .collect()
+    }
+
+    fn merge_delta(&mut self, delta: Self::Delta) {
+        for Entry { id, dot, val, meta } in delta {
+            self.insert(id, dot, val, meta);
+        }
+    }
+}
+
+impl<T: Clone, A: Actor, M: Clone> OpId<A> for LSeq<T, A, M> {
+    fn op_id(op: &Self::Op) -> Dot<A> {
+        op.dot().clone()
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T: Arbitrary + Clone, A: Actor + Arbitrary> Arbitrary for LSeq<T, A> {
+    fn arbitrary<G: Gen>(g: &mut G) -> Self {
+        let mut seq = LSeq::new(A::arbitrary(g));
+
+        for _ in 0..u8::arbitrary(g) % 20 {
+            // bias the history towards inserts so that we build up some content
+            if seq.is_empty() || u8::arbitrary(g) % 3 > 0 {
+                let ix = usize::arbitrary(g) % (seq.len() + 1);
+                seq.insert_index(ix, T::arbitrary(g));
+            } else {
+                let ix = usize::arbitrary(g) % seq.len();
+                seq.delete_index(ix);
+            }
+        }
+
+        seq
+    }
+
+    fn shrink(&self) -> Box<dyn Iterator<Item = Self>> {
+        let mut shrunk_seqs = Vec::new();
+        for i in 0..self.seq.len() {
+            let mut shrunk = self.clone();
+            shrunk.seq.remove(i);
+            shrunk_seqs.push(shrunk);
+        }
+
+        Box::new(shrunk_seqs.into_iter())
+    }
 }
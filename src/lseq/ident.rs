@@ -1,5 +1,10 @@
 use crate::Actor;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
 use bitvec::vec::*;
+use core::fmt;
+#[cfg(feature = "std")]
 use rand::Rng;
 use serde::{Deserialize, Serialize};
 
@@ -18,6 +23,95 @@ pub struct Identifier<A: Actor> {
     path: Vec<(u64, Option<A>)>,
 }
 
+impl<A: Actor> Identifier<A> {
+    /// The depth of this identifier in the exponential tree, i.e. the length of its path.
+    pub fn depth(&self) -> usize {
+        self.path.len()
+    }
+
+    /// Encode this identifier as bytes whose lexicographic order matches the order of
+    /// identifiers, so that elements can be sorted by key outside of the LSeq.
+    ///
+    /// Each level of the path is written as a `1` marker, the big-endian child index and
+    /// the (optional) site id, and the path is terminated by a `0`, so that a prefix sorts
+    /// before any of its extensions.
+    pub fn order_key(&self) -> Vec<u8>
+    where
+        A: OrderedBytes,
+    {
+        let mut key = Vec::new();
+        for (ix, site) in self.path.iter() {
+            key.push(1);
+            key.extend_from_slice(&ix.to_be_bytes());
+            match site {
+                None => key.push(0),
+                Some(site) => {
+                    key.push(1);
+                    site.write_ordered(&mut key);
+                }
+            }
+        }
+        key.push(0);
+        key
+    }
+}
+
+/// Actors that can be written as bytes whose lexicographic order matches their `Ord`.
+///
+/// The encoding must also be prefix free, i.e. no actor's bytes may be a prefix of
+/// another's, otherwise the bytes that follow it in an order key would be compared
+/// against the longer actor.
+pub trait OrderedBytes {
+    /// Append the order preserving encoding of `self` to `out`.
+    fn write_ordered(&self, out: &mut Vec<u8>);
+}
+
+macro_rules! impl_ordered_bytes {
+    ($($t:ty),*) => {
+        $(impl OrderedBytes for $t {
+            fn write_ordered(&self, out: &mut Vec<u8>) {
+                out.extend_from_slice(&self.to_be_bytes());
+            }
+        })*
+    };
+}
+
+impl_ordered_bytes!(u8, u16, u32, u64, u128, usize);
+
+/// Strings are written with their `0` bytes escaped as `0 0xff` and terminated by `0 0`.
+impl OrderedBytes for String {
+    fn write_ordered(&self, out: &mut Vec<u8>) {
+        for byte in self.bytes() {
+            out.push(byte);
+            if byte == 0 {
+                out.push(0xff);
+            }
+        }
+        out.extend_from_slice(&[0, 0]);
+    }
+}
+
+/// How an [`IdentGen`] picks a child index when allocating at a given depth of the tree.
+///
+/// `BoundaryPlus` allocates close to the lower bound, leaving room after the new
+/// identifier, which suits appends. `BoundaryMinus` allocates close to the upper bound,
+/// which suits prepends. The LSEQ paper mixes the two across depths so that neither
+/// editing pattern makes identifiers grow quickly.
+#[derive(
+    Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, Hash, PartialOrd, Ord,
+)]
+pub enum AllocStrategy {
+    /// Always allocate close to the lower bound
+    BoundaryPlus,
+    /// Always allocate close to the upper bound
+    BoundaryMinus,
+    /// Pick boundary+ or boundary- at random for each depth, the first time that depth is used
+    #[default]
+    Random,
+    /// Use boundary+ on even depths and boundary- on odd depths
+    Alternating,
+}
+
 /// A generator for fresh identifiers.
 ///
 /// These identifiers represent a path in an exponential tree. At each level of the tree the amount
@@ -32,13 +126,56 @@ pub struct IdentGen<A: Actor> {
     initial_base_bits: u8,
     /// Boundary for choosing a new number when allocating an identifier
     boundary: u64,
-    /// We keep a cache of the strategy chosen for each level of the tree
-    #[serde(skip)] // TODO: implement serialize/deserialize
+    /// Strategy used to choose between boundary+ and boundary- at each level of the tree
+    #[serde(default)]
+    alloc_strategy: AllocStrategy,
+    /// For the `Random` strategy we keep a cache of the strategy chosen for each level of the tree
+    #[serde(default, with = "strategy_serde")]
     strategy_vec: BitVec,
     /// Site id of the trees generated by this generator
     pub site_id: A,
 }
 
+// BitVec's Debug impl is not safe to call on an empty BitVec, so we print the strategies as bools
+impl<A: Actor + fmt::Debug> fmt::Debug for IdentGen<A> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("IdentGen")
+            .field("initial_base_bits", &self.initial_base_bits)
+            .field("boundary", &self.boundary)
+            .field("alloc_strategy", &self.alloc_strategy)
+            .field(
+                "strategy_vec",
+                &strategy_serde::to_bools(&self.strategy_vec),
+            )
+            .field("site_id", &self.site_id)
+            .finish()
+    }
+}
+
+// BitVec doesn't implement serde for us, so we (de)serialize the strategies as a list of bools
+mod strategy_serde {
+    use alloc::vec::Vec;
+    use bitvec::vec::BitVec;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn to_bools(strategies: &BitVec) -> Vec<bool> {
+        (0..strategies.len()).map(|i| strategies[i]).collect()
+    }
+
+    pub fn serialize<S: Serializer>(strategies: &BitVec, serializer: S) -> Result<S::Ok, S::Error> {
+        to_bools(strategies).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<BitVec, D::Error> {
+        let bools = Vec::<bool>::deserialize(deserializer)?;
+        let mut strategies = BitVec::new();
+        for b in bools {
+            strategies.push(b);
+        }
+        Ok(strategies)
+    }
+}
+
 impl<A: Actor> IdentGen<A> {
     /// Create a fresh tree with 0 node.
     pub fn new(site_id: A) -> Self {
@@ -50,11 +187,25 @@ impl<A: Actor> IdentGen<A> {
         IdentGen {
             initial_base_bits: base,
             boundary,
+            alloc_strategy: AllocStrategy::default(),
             strategy_vec: BitVec::new(),
             site_id,
         }
     }
 
+    /// Create a tree that allocates identifiers with the given strategy.
+    pub fn with_strategy(site_id: A, strategy: AllocStrategy) -> Self {
+        IdentGen {
+            alloc_strategy: strategy,
+            ..Self::new(site_id)
+        }
+    }
+
+    /// The strategy this generator allocates identifiers with.
+    pub fn alloc_strategy(&self) -> AllocStrategy {
+        self.alloc_strategy
+    }
+
     /// The smallest possible node in a tree.
     pub fn lower(&self) -> Identifier<A> {
         Identifier {
@@ -106,8 +257,10 @@ impl<A: Actor> IdentGen<A> {
                         let next_index = self.index_in_range(a.0 + 1, b.0, depth);
                         return self.replace_last(p, depth, next_index);
                     } else {
-                        // If we end up here that means that a + 1 = b. Therefore we can't actually
-                        // insert a new node at this layer. So we start a new layer below a.
+                        // If we end up here then either a + 1 = b and the two nodes are adjacent,
+                        // or an earlier layer had equal indices ordered by site id so that a >= b.
+                        // Either way there's no room for a new node at this layer, so we start a
+                        // new layer below a. Everything below a is still smaller than q.
                         return self.alloc_from_lower(p, depth + 1);
                     }
                 }
@@ -228,11 +381,10 @@ impl<A: Actor> IdentGen<A> {
             upper
         );
 
-        let mut rng = rand::rngs::OsRng;
-        let interval = std::cmp::min(self.boundary, upper - lower);
+        let interval = core::cmp::min(self.boundary, upper - lower);
 
         let step = if interval > 0 {
-            rng.gen_range(0, interval)
+            self.random_below(interval, depth, lower)
         } else {
             0
         };
@@ -251,19 +403,41 @@ impl<A: Actor> IdentGen<A> {
         index
     }
 
+    // Returns true for boundary+ and false for boundary- at the given depth.
+    // `usize::is_multiple_of` needs a newer compiler than we support.
+    #[allow(clippy::manual_is_multiple_of)]
     fn strategy(&mut self, depth: usize) -> bool {
-        match self.strategy_vec.get(depth) {
-            None => {
-                let new_strategy = rand::thread_rng().gen();
-                self.strategy_vec.push(new_strategy);
-                new_strategy
-            }
-            Some(s) => *s,
+        match self.alloc_strategy {
+            AllocStrategy::BoundaryPlus => true,
+            AllocStrategy::BoundaryMinus => false,
+            AllocStrategy::Alternating => depth % 2 == 0,
+            AllocStrategy::Random => match self.strategy_vec.get(depth) {
+                None => {
+                    let new_strategy = self.random_below(2, depth, 0) == 0;
+                    self.strategy_vec.push(new_strategy);
+                    new_strategy
+                }
+                Some(s) => *s,
+            },
         }
-        // temp strategy. Should be a random choice at each level
+    }
+
+    // A random number in [0, bound) for an allocation at `depth` starting at `lower`.
+    #[cfg(feature = "std")]
+    fn random_below(&self, bound: u64, _depth: usize, _lower: u64) -> u64 {
+        rand::rngs::OsRng.gen_range(0, bound)
+    }
+
+    // Without `std` there is no entropy source, so the allocation's position is hashed with
+    // the site id instead. Identifiers embed the site id and stay unique either way, the
+    // number only needs to spread allocations out.
+    #[cfg(not(feature = "std"))]
+    fn random_below(&self, bound: u64, depth: usize, lower: u64) -> u64 {
+        crate::fnv::hash_of(&(&self.site_id, depth, lower)) % bound
     }
 }
 
+#[cfg(feature = "std")]
 impl<A: quickcheck::Arbitrary + Actor> quickcheck::Arbitrary for Identifier<A> {
     fn arbitrary<G: quickcheck::Gen>(g: &mut G) -> Identifier<A> {
         let max_depth = 27; // TODO: where does this come from?
@@ -289,16 +463,16 @@ impl<A: quickcheck::Arbitrary + Actor> quickcheck::Arbitrary for Identifier<A> {
 
     fn shrink(&self) -> Box<dyn Iterator<Item = Self>> {
         if self.path.len() == 1 {
-            Box::new(std::iter::empty())
+            Box::new(core::iter::empty())
         } else {
             let mut path = self.path.clone();
             path.pop();
-            Box::new(std::iter::once(Self { path }))
+            Box::new(core::iter::once(Self { path }))
         }
     }
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "std"))]
 mod test {
     use super::*;
     use quickcheck::{quickcheck, TestResult};
@@ -316,6 +490,14 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_decode_4x_json() {
+        // 4.x neither had an allocation strategy nor serialized the strategy cache
+        let json = r#"{"initial_base_bits":3,"boundary":10,"site_id":1}"#;
+        let gen: IdentGen<u8> = serde_json::from_str(json).unwrap();
+        assert_eq!(gen, IdentGen::new_with_args(1, 3, 10));
+    }
+
     #[test]
     fn test_alloc_eq_path() {
         let mut gen = IdentGen::new(0);
@@ -328,7 +510,6 @@ mod test {
         };
         gen.alloc(&x, &y);
         let b = gen.alloc(&x, &y);
-        // println!("{:?} {:?} {:?}", x, b, y);
         assert!(x < b);
         assert!(b < y);
     }
@@ -426,6 +607,29 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_alloc_between_adjacent_ids_deepens_tree() {
+        let mut gen = IdentGen::new(0);
+        let p = Identifier {
+            path: vec![(1, Some(0))],
+        };
+        let mut q = Identifier {
+            path: vec![(2, Some(0))],
+        };
+
+        // there's no room between adjacent nodes, so each allocation must go deeper
+        let mut allocated = vec![p.clone(), q.clone()];
+        for _ in 0..20 {
+            let z = gen.alloc(&p, &q);
+            assert!(p < z);
+            assert!(z < q);
+            assert!(z.depth() > 1);
+            assert!(!allocated.contains(&z));
+            allocated.push(z.clone());
+            q = z;
+        }
+    }
+
     #[test]
     fn test_index_in_range() {
         let mut gen = IdentGen::new(0);
@@ -1,6 +1,7 @@
 use std::cmp::Ordering;
 use std::collections::HashMap;
 use std::collections::{BTreeMap, BTreeSet};
+use std::convert::Infallible;
 use std::mem;
 
 use serde::{Deserialize, Serialize};
@@ -83,11 +84,11 @@ impl<K: Ord, V: Val<A> + Default, A: Actor> Default for Map<K, V, A> {
 
 impl<K: Ord, V: Val<A> + Default, A: Actor> Causal<A> for Map<K, V, A> {
     fn forget(&mut self, clock: &VClock<A>) {
-        self.entries = mem::replace(&mut self.entries, BTreeMap::new())
+        self.entries = mem::take(&mut self.entries)
             .into_iter()
             .filter_map(|(key, mut entry)| {
-                entry.clock.forget(&clock);
-                entry.val.forget(&clock);
+                entry.clock.forget(clock);
+                entry.val.forget(clock);
                 if entry.clock.is_empty() {
                     None // remove this entry since its been forgotten
                 } else {
@@ -96,10 +97,10 @@ impl<K: Ord, V: Val<A> + Default, A: Actor> Causal<A> for Map<K, V, A> {
             })
             .collect();
 
-        self.deferred = mem::replace(&mut self.deferred, HashMap::new())
+        self.deferred = mem::take(&mut self.deferred)
             .into_iter()
             .filter_map(|(mut rm_clock, key)| {
-                rm_clock.forget(&clock);
+                rm_clock.forget(clock);
                 if rm_clock.is_empty() {
                     None // this deferred remove has been forgotten
                 } else {
@@ -108,12 +109,13 @@ impl<K: Ord, V: Val<A> + Default, A: Actor> Causal<A> for Map<K, V, A> {
             })
             .collect();
 
-        self.clock.forget(&clock);
+        self.clock.forget(clock);
     }
 }
 
 impl<K: Ord, V: Val<A> + Default, A: Actor> CmRDT for Map<K, V, A> {
     type Op = Op<K, V, A>;
+    type Validation = Infallible;
 
     fn apply(&mut self, op: Self::Op) {
         match op {
@@ -138,7 +140,7 @@ impl<K: Ord, V: Val<A> + Default, A: Actor> CmRDT for Map<K, V, A> {
 
 impl<K: Ord, V: Val<A> + Default, A: Actor> CvRDT for Map<K, V, A> {
     fn merge(&mut self, other: Self) {
-        self.entries = mem::replace(&mut self.entries, BTreeMap::new())
+        self.entries = mem::take(&mut self.entries)
             .into_iter()
             .filter_map(|(key, mut entry)| {
                 if !other.entries.contains_key(&key) {
@@ -249,7 +251,7 @@ impl<K: Ord, V: Val<A> + Default, A: Actor> Map<K, V, A> {
     /// Retrieve value stored under a key
     pub fn get(&self, key: &K) -> ReadCtx<Option<V>, A> {
         let add_clock = self.clock.clone();
-        let entry_opt = self.entries.get(&key);
+        let entry_opt = self.entries.get(key);
         ReadCtx {
             add_clock,
             rm_clock: entry_opt
@@ -275,7 +277,7 @@ impl<K: Ord, V: Val<A> + Default, A: Actor> Map<K, V, A> {
         let key = key.into();
         let dot = ctx.dot.clone();
         let op = match self.entries.get(&key).map(|e| &e.val) {
-            Some(data) => f(&data, ctx),
+            Some(data) => f(data, ctx),
             None => f(&V::default(), ctx),
         };
 
@@ -307,7 +309,7 @@ impl<K: Ord, V: Val<A> + Default, A: Actor> Map<K, V, A> {
 
     /// apply the pending deferred removes
     fn apply_deferred(&mut self) {
-        let deferred = mem::replace(&mut self.deferred, HashMap::new());
+        let deferred = mem::take(&mut self.deferred);
         for (clock, keys) in deferred {
             self.apply_keyset_rm(keys, clock);
         }
@@ -316,12 +318,12 @@ impl<K: Ord, V: Val<A> + Default, A: Actor> Map<K, V, A> {
     /// Apply a set of key removals given a clock.
     fn apply_keyset_rm(&mut self, mut keyset: BTreeSet<K>, clock: VClock<A>) {
         for key in keyset.iter() {
-            if let Some(entry) = self.entries.get_mut(&key) {
+            if let Some(entry) = self.entries.get_mut(key) {
                 entry.clock.forget(&clock);
                 if entry.clock.is_empty() {
                     // The entry clock says we have no info on this entry.
                     // So remove the entry
-                    self.entries.remove(&key);
+                    self.entries.remove(key);
                 } else {
                     // The entry clock is not empty so this means we still
                     // have some information on this entry, keep it.
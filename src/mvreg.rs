@@ -1,11 +1,20 @@
-use std::cmp::Ordering;
-use std::fmt::{self, Display};
-use std::mem;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cmp::{self, Ordering};
+use core::fmt::{self, Display};
+use core::hash::{Hash, Hasher};
+use core::marker::PhantomData;
+use core::mem;
+#[cfg(feature = "std")]
+use std::{collections::hash_map::DefaultHasher, error, string::String};
+
+#[cfg(not(feature = "std"))]
+use crate::fnv::FnvHasher as DefaultHasher;
 
 use serde::{Deserialize, Serialize};
 
-use crate::ctx::{AddCtx, ReadCtx};
-use crate::{Actor, Causal, CmRDT, CvRDT, VClock};
+use crate::ctx::{AddCtx, ReadCtx, RmCtx};
+use crate::{Actor, Causal, CausalContext, CmRDT, CvRDT, DeltaCmRDT, Dot, Snapshot, VClock};
 
 /// MVReg (Multi-Value Register)
 /// On concurrent writes, we will keep all values for which
@@ -28,9 +37,131 @@ use crate::{Actor, Causal, CmRDT, CvRDT, VClock};
 /// // Since "bob" and "alice" were added concurrently, we see both on read
 /// assert_eq!(r1.read().val, vec!["bob", "alice"]);
 /// ```
+///
+/// The values' causal context defaults to a `VClock`; any other `CausalContext` can be
+/// used for state based replication through `put` and `merge`. Ops, reads and
+/// `forget` are only available for `VClock` contexts.
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct MVReg<V, A: Actor> {
-    vals: Vec<(VClock<A>, V)>,
+#[serde(
+    from = "State<V, C>",
+    bound(deserialize = "V: Deserialize<'de>, C: Deserialize<'de>")
+)]
+pub struct MVReg<V, A: Actor, C: CausalContext = VClock<A>> {
+    vals: Vec<(C, V)>,
+    /// history we've been told to forget, puts entirely within it are stale
+    #[serde(default)]
+    forgotten: C,
+    /// cache of the merge of all value clocks and the forgotten history, kept in sync on
+    /// every mutation. Contexts derived from reads must cover forgotten history, otherwise
+    /// a write could reuse a dot that a forgotten or cleared value was written with.
+    #[serde(skip)]
+    clock: C,
+    /// application defined version of the values' schema, see `MVReg::migrate`
+    #[serde(default)]
+    schema_version: u32,
+    /// audit log of conflict resolutions, `None` unless enabled, never replicated
+    #[serde(skip)]
+    resolutions: Option<ResolutionLog<V, C>>,
+    #[serde(skip)]
+    actor: PhantomData<A>,
+}
+
+/// An entry of the `MVReg` resolution log, see `MVReg::enable_resolution_log`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Resolution<V, C> {
+    /// The clock the chosen value was stored under
+    pub clock: C,
+    /// The value that replaced the concurrent values
+    pub chosen: V,
+    /// The concurrent values that were replaced
+    pub discarded: Vec<V>,
+}
+
+/// The resolution log of an `MVReg`, along with the function copying the values it
+/// records. Keeping the copy here rather than bounding the register on `V: Clone` lets
+/// registers of values that can't be cloned be used without the log.
+#[derive(Clone)]
+struct ResolutionLog<V, C> {
+    entries: Vec<Resolution<V, C>>,
+    copy: fn(&V) -> V,
+}
+
+impl<V: fmt::Debug, C: fmt::Debug> fmt::Debug for ResolutionLog<V, C> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.entries.fmt(f)
+    }
+}
+
+/// The provenance of one of the values of an `MVReg`, see `MVReg::explain`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BranchInfo<'a, V, A> {
+    /// The value written on this branch
+    pub value: &'a V,
+    /// The actors whose writes the value has seen, with their counters, in actor order
+    pub actors: Vec<(A, u64)>,
+    /// `true` if the value was written concurrently with another value of the register
+    pub concurrent: bool,
+}
+
+/// The plain data state of an MVReg, see `Snapshot`. Registers are also deserialized
+/// through this, the cached clock is rebuilt from it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct State<V, C> {
+    /// the concurrent values along with the clocks they were written at
+    pub vals: Vec<(C, V)>,
+    /// history the register has forgotten or cleared
+    #[serde(default)]
+    pub forgotten: C,
+    /// application defined version of the values' schema
+    #[serde(default)]
+    pub schema_version: u32,
+}
+
+impl<V, A: Actor, C: CausalContext> From<State<V, C>> for MVReg<V, A, C> {
+    fn from(raw: State<V, C>) -> Self {
+        let mut reg = Self {
+            vals: raw.vals,
+            forgotten: raw.forgotten,
+            clock: C::default(),
+            schema_version: raw.schema_version,
+            resolutions: None,
+            actor: PhantomData,
+        };
+        reg.refresh_clock();
+        reg
+    }
+}
+
+/// The state of an MVReg as encoded by crdts 4.x and earlier, which only stored the
+/// values.
+///
+/// Registers are now encoded as a `State`. Self-describing formats such as JSON still
+/// decode the old layout directly, but formats that aren't, such as bincode, need old
+/// bytes decoded as a `LegacyState` and converted:
+///
+/// ```rust
+/// use crdts::{mvreg::LegacyState, Dot, MVReg, VClock};
+/// # let old_reg = LegacyState { vals: vec![(VClock::from(Dot::new(1u8, 1)), 7u8)] };
+/// # let old_bytes = bincode::serialize(&old_reg).unwrap();
+/// let old: LegacyState<u8, VClock<u8>> = bincode::deserialize(&old_bytes).unwrap();
+/// let reg: MVReg<u8, u8> = old.into();
+/// assert_eq!(reg.read().val, vec![7]);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LegacyState<V, C> {
+    /// the concurrent values along with the clocks they were written at
+    pub vals: Vec<(C, V)>,
+}
+
+impl<V, A: Actor, C: CausalContext> From<LegacyState<V, C>> for MVReg<V, A, C> {
+    fn from(legacy: LegacyState<V, C>) -> Self {
+        State {
+            vals: legacy.vals,
+            forgotten: C::default(),
+            schema_version: 0,
+        }
+        .into()
+    }
 }
 
 /// Defines the set of operations over the MVReg
@@ -43,6 +174,119 @@ pub enum Op<V, A: Actor> {
         /// the value to put
         val: V,
     },
+    /// Remove every value whose history is covered by the clock
+    Clear {
+        /// context of the operation, usually the read context's rm clock
+        clock: VClock<A>,
+    },
+}
+
+impl<V, A: Actor> Op<V, A> {
+    /// The clock of the context the op was generated in
+    pub fn clock(&self) -> &VClock<A> {
+        match self {
+            Op::Put { clock, .. } | Op::Clear { clock } => clock,
+        }
+    }
+
+    /// The value written by the op, `None` for a `Clear`
+    pub fn value(&self) -> Option<&V> {
+        match self {
+            Op::Put { val, .. } => Some(val),
+            Op::Clear { .. } => None,
+        }
+    }
+}
+
+/// Values that record the wall-clock time they were written at.
+///
+/// This gives an `MVReg` holding such values an optional last-writer-wins view
+/// through `MVReg::latest_by_time`.
+pub trait Timestamped {
+    /// The type of the timestamp, e.g. milliseconds since the unix epoch
+    type Time: Ord;
+
+    /// The time this value was written at
+    fn timestamp(&self) -> Self::Time;
+}
+
+/// Error returned by `MVReg::validate_op` for malformed puts
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MalformedOp {
+    /// The put reuses the clock of a value already in the register, but with a different
+    /// value. Applying it keeps both values under the same clock, which no well-formed
+    /// history produces.
+    ConflictingPut,
+    /// The put has an empty clock, so `apply` ignores it
+    EmptyClock,
+}
+
+#[cfg(feature = "std")]
+impl error::Error for MalformedOp {}
+
+impl Display for MalformedOp {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MalformedOp::ConflictingPut => {
+                write!(f, "put reuses the clock of a different stored value")
+            }
+            MalformedOp::EmptyClock => write!(f, "put has an empty clock"),
+        }
+    }
+}
+
+/// Error returned by `MVReg::cas` when the register has moved on from the expected clock
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CasError<A: Actor> {
+    /// the register's clock at the time of the compare
+    pub current: VClock<A>,
+}
+
+#[cfg(feature = "std")]
+impl<A: Actor + fmt::Debug> error::Error for CasError<A> {}
+
+impl<A: Actor + fmt::Debug> Display for CasError<A> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "register clock {:?} does not match the expected clock",
+            self.current
+        )
+    }
+}
+
+/// Error returned by `MVReg::try_set` when a value can't be written under a size budget
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SizeError {
+    /// The serialized value is larger than the budget
+    Oversized {
+        /// serialized size of the value in bytes
+        size: u64,
+        /// the budget the value was checked against
+        max_bytes: usize,
+    },
+    /// The value could not be serialized to measure its size
+    Unserializable(String),
+}
+
+#[cfg(feature = "std")]
+impl error::Error for SizeError {}
+
+#[cfg(feature = "std")]
+impl Display for SizeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SizeError::Oversized { size, max_bytes } => write!(
+                f,
+                "value serializes to {} bytes which exceeds the budget of {} bytes",
+                size, max_bytes
+            ),
+            SizeError::Unserializable(reason) => {
+                write!(f, "failed to serialize value: {}", reason)
+            }
+        }
+    }
 }
 
 impl<V: Display, A: Actor + Display> Display for MVReg<V, A> {
@@ -58,7 +302,7 @@ impl<V: Display, A: Actor + Display> Display for MVReg<V, A> {
     }
 }
 
-impl<V: PartialEq, A: Actor> PartialEq for MVReg<V, A> {
+impl<V: PartialEq, A: Actor, C: CausalContext> PartialEq for MVReg<V, A, C> {
     fn eq(&self, other: &Self) -> bool {
         for dot in self.vals.iter() {
             let num_found = other.vals.iter().filter(|d| d == &dot).count();
@@ -82,92 +326,385 @@ impl<V: PartialEq, A: Actor> PartialEq for MVReg<V, A> {
     }
 }
 
-impl<V: Eq, A: Actor> Eq for MVReg<V, A> {}
+impl<V: Eq, A: Actor, C: CausalContext + Eq> Eq for MVReg<V, A, C> {}
 
-impl<V: Clone, A: Actor> Causal<A> for MVReg<V, A> {
-    fn forget(&mut self, clock: &VClock<A>) {
-        self.vals = self
-            .vals
-            .clone()
-            .into_iter()
-            .filter_map(|(mut val_clock, val)| {
-                val_clock.forget(&clock);
-                if val_clock.is_empty() {
-                    None // remove this value from the register
-                } else {
-                    Some((val_clock, val))
-                }
+/// Consistent with `PartialEq`: only the stored `(clock, value)` pairs are hashed, and
+/// since equality ignores the order they are stored in, the per-pair hashes are combined
+/// with a wrapping sum so that registers holding the same pairs in a different order
+/// hash the same.
+impl<V: Hash, A: Actor, C: CausalContext + Hash> Hash for MVReg<V, A, C> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        let combined = self.vals.iter().fold(0u64, |acc, val| {
+            let mut hasher = DefaultHasher::default();
+            val.hash(&mut hasher);
+            acc.wrapping_add(hasher.finish())
+        });
+        self.vals.len().hash(state);
+        combined.hash(state);
+    }
+}
+
+/// Registers are ordered by the merge lattice: `a <= b` when merging `a` into `b`
+/// leaves `b` unchanged, i.e. every value of `a` is also in `b` or dominated by a
+/// value of `b`. Registers that each hold values the other hasn't seen are concurrent.
+impl<V: PartialEq, A: Actor, C: CausalContext> PartialOrd for MVReg<V, A, C> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        let subsumed_by = |a: &Self, b: &Self| {
+            a.vals.iter().all(|(clock, val)| {
+                b.vals
+                    .iter()
+                    .any(|(c, v)| clock < c || (clock == c && val == v))
             })
-            .collect()
+        };
+
+        match (subsumed_by(self, other), subsumed_by(other, self)) {
+            (true, true) => Some(Ordering::Equal),
+            (true, false) => Some(Ordering::Less),
+            (false, true) => Some(Ordering::Greater),
+            (false, false) => None,
+        }
+    }
+}
+
+impl<V, A: Actor> Causal<A> for MVReg<V, A> {
+    fn forget(&mut self, clock: &VClock<A>) {
+        self.forgotten.merge(clock.clone());
+        self.vals.retain_mut(|(val_clock, _)| {
+            val_clock.forget(clock);
+            // values whose entire history is forgotten are removed from the register
+            !val_clock.is_empty()
+        });
+        self.refresh_clock();
+    }
+}
+
+impl<V: Clone, A: Actor, C: CausalContext> Snapshot for MVReg<V, A, C> {
+    type State = State<V, C>;
+
+    fn snapshot(&self) -> State<V, C> {
+        State {
+            vals: self.vals.clone(),
+            forgotten: self.forgotten.clone(),
+            schema_version: self.schema_version,
+        }
+    }
+
+    /// The resolution log is local to a replica and isn't part of the state, the
+    /// restored register starts without one.
+    fn restore(state: State<V, C>) -> Self {
+        Self::from(state)
+    }
+}
+
+/// Consumes the register, yielding each concurrent value along with its clock
+impl<V, A: Actor, C: CausalContext> IntoIterator for MVReg<V, A, C> {
+    type Item = (C, V);
+    type IntoIter = vec::IntoIter<(C, V)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.vals.into_iter()
     }
 }
 
-impl<V, A: Actor> Default for MVReg<V, A> {
+impl<V, A: Actor, C: CausalContext> Default for MVReg<V, A, C> {
     fn default() -> Self {
-        Self::new()
+        Self {
+            vals: Vec::new(),
+            forgotten: C::default(),
+            clock: C::default(),
+            schema_version: 0,
+            resolutions: None,
+            actor: PhantomData,
+        }
     }
 }
 
-impl<V, A: Actor> CvRDT for MVReg<V, A> {
+impl<V: PartialEq, A: Actor, C: CausalContext> CvRDT for MVReg<V, A, C> {
     fn merge(&mut self, other: Self) {
-        self.vals = mem::replace(&mut self.vals, Vec::new())
-            .into_iter()
-            .filter(|(clock, _)| other.vals.iter().filter(|(c, _)| clock < c).count() == 0)
-            .collect();
+        self.forgotten.join(&other.forgotten);
+        self.vals
+            .retain(|(clock, _)| !other.vals.iter().any(|(c, _)| clock < c));
 
-        self.vals.extend(
-            other
-                .vals
-                .into_iter()
-                .filter(|(clock, _)| self.vals.iter().filter(|(c, _)| clock < c).count() == 0)
-                .filter(|(clock, _)| self.vals.iter().all(|(c, _)| clock != c))
-                .collect::<Vec<_>>(),
-        );
+        // values are moved out of `other`, and only compared against our own values.
+        // Like `put`, a value stored under the same clock as a different value is kept.
+        let len = self.vals.len();
+        for (clock, val) in other.vals {
+            let seen = self.vals[..len]
+                .iter()
+                .any(|(c, v)| &clock < c || (&clock == c && v == &val));
+            if !seen {
+                self.vals.push((clock, val));
+            }
+        }
+
+        // values only get dropped when another value dominates them, so the
+        // merged clock is just the merge of both clocks
+        self.clock.join(&other.clock);
+
+        // a value one side has cleared must not resurface from the other side
+        self.drop_forgotten();
+    }
+
+    fn bottom() -> Self {
+        Self::default()
+    }
+
+    fn is_bottom(&self) -> bool {
+        self.vals.is_empty()
     }
 }
 
-impl<V, A: Actor> CmRDT for MVReg<V, A> {
+impl<V: PartialEq, A: Actor> CmRDT for MVReg<V, A> {
     type Op = Op<V, A>;
+    type Validation = MalformedOp;
 
-    fn apply(&mut self, op: Self::Op) {
+    fn validate_op(&self, op: &Self::Op) -> Result<(), MalformedOp> {
         match op {
+            Op::Put { clock, .. } if clock.is_empty() => Err(MalformedOp::EmptyClock),
             Op::Put { clock, val } => {
-                if clock.is_empty() {
-                    return;
+                let conflicting = self.vals.iter().any(|(c, v)| c == clock && v != val);
+                if conflicting {
+                    Err(MalformedOp::ConflictingPut)
+                } else {
+                    Ok(())
                 }
-                // first filter out all values that are dominated by the Op clock
-                self.vals
-                    .retain(|(val_clock, _)| match val_clock.partial_cmp(&clock) {
-                        None | Some(Ordering::Greater) => true,
-                        _ => false,
-                    });
+            }
+            Op::Clear { .. } => Ok(()),
+        }
+    }
 
-                // TAI: in the case were the Op has a context that already was present,
-                //      the above line would remove that value, the next lines would
-                //      keep the val from the Op, so.. a malformed Op could break
-                //      commutativity.
-
-                // now check if we've already seen this op
-                let mut should_add = true;
-                for (existing_clock, _) in self.vals.iter() {
-                    if existing_clock > &clock {
-                        // we've found an entry that dominates this op
-                        should_add = false;
+    fn apply(&mut self, op: Self::Op) {
+        match op {
+            Op::Put { clock, val } => self.put(clock, val),
+            Op::Clear { clock } => self.apply_rm(&clock),
+        }
+    }
+
+    /// The batch is checked up front against the values as they will be when each op is
+    /// applied, tracked by reference along with the history cleared so far, so it is
+    /// validated without staging it on a clone.
+    fn apply_all(&mut self, ops: &[Self::Op]) -> Result<(), (usize, MalformedOp)>
+    where
+        Self: Clone,
+        Self::Op: Clone,
+    {
+        let mut forgotten = self.forgotten.clone();
+        let mut vals: Vec<(&VClock<A>, &V)> = self.vals.iter().map(|(c, v)| (c, v)).collect();
+        for (i, op) in ops.iter().enumerate() {
+            match op {
+                Op::Put { clock, .. } if clock.is_empty() => {
+                    return Err((i, MalformedOp::EmptyClock));
+                }
+                Op::Put { clock, val } => {
+                    if vals.iter().any(|(c, v)| *c == clock && *v != val) {
+                        return Err((i, MalformedOp::ConflictingPut));
+                    }
+                    // mirrors `put`, stale and redelivered puts leave the values alone
+                    if clock <= &forgotten || vals.iter().any(|(c, _)| *c == clock) {
+                        continue;
+                    }
+                    vals.retain(|(c, _)| !matches!((*c).partial_cmp(clock), Some(Ordering::Less)));
+                    if !vals.iter().any(|(c, _)| *c > clock) {
+                        vals.push((clock, val));
                     }
                 }
-
-                if should_add {
-                    self.vals.push((clock, val));
+                // mirrors `apply_rm`
+                Op::Clear { clock } => {
+                    forgotten.merge(clock.clone());
+                    vals.retain(|(c, _)| {
+                        !matches!(
+                            (*c).partial_cmp(clock),
+                            Some(Ordering::Less) | Some(Ordering::Equal)
+                        )
+                    });
                 }
             }
         }
+        for op in ops {
+            self.apply(op.clone());
+        }
+        Ok(())
+    }
+}
+
+impl<V: Clone + PartialEq, A: Actor> DeltaCmRDT<A> for MVReg<V, A> {
+    type Delta = Self;
+
+    /// The delta holds the values not dominated by `since`, along with everything this
+    /// register has cleared so that clears reach the peer too.
+    fn delta(&self, since: &VClock<A>) -> Self {
+        Self::from(State {
+            vals: self
+                .vals
+                .iter()
+                .filter(|(c, _)| matches!(c.partial_cmp(since), None | Some(Ordering::Greater)))
+                .cloned()
+                .collect(),
+            forgotten: self.forgotten.clone(),
+            schema_version: self.schema_version,
+        })
+    }
+
+    fn merge_delta(&mut self, delta: Self) {
+        self.merge(delta);
+    }
+}
+
+impl<V, A: Actor, C: CausalContext> MVReg<V, A, C> {
+    /// Store `val` under the causal context `clock`, replacing the values it dominates.
+    ///
+    /// This is what applying a `Put` op does, for registers whose context isn't a `VClock`.
+    pub fn put(&mut self, clock: C, val: V)
+    where
+        V: PartialEq,
+    {
+        if clock.is_empty() {
+            return;
+        }
+        // a put whose history we've entirely forgotten would resurrect a
+        // value that `forget` already dropped
+        if clock <= self.forgotten {
+            return;
+        }
+        // a redelivered op
+        if self.vals.iter().any(|(c, v)| c == &clock && v == &val) {
+            return;
+        }
+        let dominated =
+            |val_clock: &C| matches!(val_clock.partial_cmp(&clock), Some(Ordering::Less));
+        // a put over several concurrent values is a manual resolution of their conflict
+        let mut discarded = Vec::new();
+        if let Some(log) = self.resolutions.as_ref() {
+            discarded.extend(
+                self.vals
+                    .iter()
+                    .filter(|(val_clock, _)| dominated(val_clock))
+                    .map(|(_, v)| (log.copy)(v)),
+            );
+        }
+
+        // first filter out all values that are strictly dominated by the Op clock
+        self.vals.retain(|(val_clock, _)| !dominated(val_clock));
+
+        // A malformed Op may carry the clock of a stored value but a different value,
+        // `validate_op` detects such ops. Overwriting the stored value would make the
+        // result depend on delivery order, so both values are kept as if concurrent.
+
+        // now check if we've already seen this op
+        let mut should_add = true;
+        for (existing_clock, _) in self.vals.iter() {
+            if existing_clock > &clock {
+                // we've found an entry that dominates this op
+                should_add = false;
+            }
+        }
+
+        if should_add {
+            if discarded.len() > 1 {
+                self.log_resolution(&clock, &val, discarded);
+            }
+            self.clock.join(&clock);
+            self.vals.push((clock, val));
+        }
+    }
+
+    /// Start recording every conflict resolution in the resolution log.
+    ///
+    /// Both a put whose clock dominates several concurrent values and a `merge_resolving`
+    /// collapsing concurrent values are recorded. The log is local to this replica: it is
+    /// not merged, and it is not serialized.
+    pub fn enable_resolution_log(&mut self)
+    where
+        V: Clone,
+    {
+        self.resolutions.get_or_insert_with(|| ResolutionLog {
+            entries: Vec::new(),
+            copy: V::clone,
+        });
+    }
+
+    /// The conflict resolutions recorded since `enable_resolution_log`, oldest first
+    pub fn resolution_log(&self) -> &[Resolution<V, C>] {
+        self.resolutions.as_ref().map_or(&[], |log| &log.entries)
+    }
+
+    /// Append an entry to the resolution log, if it is enabled
+    fn log_resolution(&mut self, clock: &C, chosen: &V, discarded: Vec<V>) {
+        if let Some(log) = self.resolutions.as_mut() {
+            log.entries.push(Resolution {
+                clock: clock.clone(),
+                chosen: (log.copy)(chosen),
+                discarded,
+            });
+        }
+    }
+
+    /// Drop the values whose history is entirely forgotten, e.g. by a clear
+    fn drop_forgotten(&mut self) {
+        let len = self.vals.len();
+        let forgotten = &self.forgotten;
+        self.vals.retain(|(val_clock, _)| {
+            matches!(
+                val_clock.partial_cmp(forgotten),
+                None | Some(Ordering::Greater)
+            )
+        });
+        if self.vals.len() != len {
+            self.refresh_clock();
+        }
+    }
+
+    /// Rebuild the cached clock from the clocks of the values and the forgotten history
+    fn refresh_clock(&mut self) {
+        self.clock = self
+            .vals
+            .iter()
+            .fold(self.forgotten.clone(), |mut accum_clock, (c, _)| {
+                accum_clock.join(c);
+                accum_clock
+            })
     }
 }
 
 impl<V, A: Actor> MVReg<V, A> {
     /// Construct a new empty MVReg
     pub fn new() -> Self {
-        Self { vals: Vec::new() }
+        Self::default()
+    }
+
+    /// Construct a register holding `val`, as written by `actor` in its first op
+    pub fn with_value(val: V, actor: A) -> Self {
+        let clock = VClock::from(Dot::new(actor, 1));
+        Self {
+            vals: vec![(clock.clone(), val)],
+            forgotten: VClock::new(),
+            clock,
+            schema_version: 0,
+            resolutions: None,
+            actor: PhantomData,
+        }
+    }
+
+    /// Construct a register holding the given concurrent values.
+    ///
+    /// Returns `None` if any two of the clocks are causally ordered (or equal),
+    /// since such a pair could never survive together in a register.
+    pub fn from_concurrent(vals: Vec<(VClock<A>, V)>) -> Option<Self> {
+        let all_concurrent = vals.iter().enumerate().all(|(i, (clock, _))| {
+            vals[i + 1..]
+                .iter()
+                .all(|(other, _)| clock.partial_cmp(other).is_none())
+        });
+
+        if all_concurrent {
+            Some(Self::from(State {
+                vals,
+                forgotten: VClock::new(),
+                schema_version: 0,
+            }))
+        } else {
+            None
+        }
     }
 
     /// Set the value of the register
@@ -178,6 +715,140 @@ impl<V, A: Actor> MVReg<V, A> {
         }
     }
 
+    /// Remove the values the read context covers. Values written concurrently with the
+    /// clear are not covered by its clock and survive it, so a concurrent write wins.
+    pub fn clear(&self, ctx: RmCtx<A>) -> Op<V, A> {
+        Op::Clear { clock: ctx.clock }
+    }
+
+    /// Set the value of the register only if its clock is still `expected`, typically the
+    /// clock of an earlier read. If another write has been applied since, no op is produced
+    /// and the error carries the register's current clock so the caller can re-read and retry.
+    pub fn cas(
+        &self,
+        expected: &VClock<A>,
+        val: V,
+        ctx: AddCtx<A>,
+    ) -> Result<Op<V, A>, CasError<A>> {
+        if &self.clock == expected {
+            Ok(self.write(val, ctx))
+        } else {
+            Err(CasError {
+                current: self.clock(),
+            })
+        }
+    }
+
+    /// Set the value of the register, failing if the serialized value is larger than `max_bytes`
+    #[cfg(feature = "std")]
+    pub fn try_set(&self, val: V, ctx: AddCtx<A>, max_bytes: usize) -> Result<Op<V, A>, SizeError>
+    where
+        V: Serialize,
+    {
+        let size = bincode::serialized_size(&val)
+            .map_err(|err| SizeError::Unserializable(err.to_string()))?;
+
+        if size > max_bytes as u64 {
+            Err(SizeError::Oversized { size, max_bytes })
+        } else {
+            Ok(self.write(val, ctx))
+        }
+    }
+
+    /// The ops a peer that has seen everything up to `clock` needs to catch up with this
+    /// register: one `Put` for every stored value that the peer hasn't seen. Clocks don't
+    /// record which clears a peer has seen, so these are preceded by a `Clear` of everything
+    /// this register has cleared, which is a no-op for a peer that has already applied it.
+    pub fn ops_since(&self, clock: &VClock<A>) -> Vec<Op<V, A>>
+    where
+        V: Clone,
+    {
+        let clear = Some(&self.forgotten)
+            .filter(|forgotten| !forgotten.is_empty())
+            .map(|forgotten| Op::Clear {
+                clock: forgotten.clone(),
+            });
+
+        clear
+            .into_iter()
+            .chain(
+                self.vals
+                    .iter()
+                    .filter(|(c, _)| matches!(c.partial_cmp(clock), None | Some(Ordering::Greater)))
+                    .map(|(c, val)| Op::Put {
+                        clock: c.clone(),
+                        val: val.clone(),
+                    }),
+            )
+            .collect()
+    }
+
+    /// Drop the puts in an op log that are dominated by another put in the log.
+    ///
+    /// A dominated put has no effect on a register that has also seen the put dominating
+    /// it, so replaying the compacted log gives the same register as replaying the full log.
+    pub fn compact_ops(ops: Vec<Op<V, A>>) -> Vec<Op<V, A>> {
+        let is_dominated = |clock: &VClock<A>| {
+            ops.iter().any(|op| match op {
+                Op::Put { clock: c, .. } => clock < c,
+                Op::Clear { .. } => false,
+            })
+        };
+        let keep: Vec<bool> = ops
+            .iter()
+            .map(|op| match op {
+                Op::Put { clock, .. } => !is_dominated(clock),
+                Op::Clear { .. } => true,
+            })
+            .collect();
+
+        ops.into_iter()
+            .zip(keep)
+            .filter_map(|(op, keep)| if keep { Some(op) } else { None })
+            .collect()
+    }
+
+    /// Remove any stored value whose clock is dominated by another stored value's clock,
+    /// or that duplicates another stored value and clock, restoring the minimal set of
+    /// concurrent values.
+    ///
+    /// `apply` and `merge` already keep the values minimal, this is for registers built
+    /// from malformed input, e.g. a deserialized register holding dominated values.
+    pub fn compact(&mut self)
+    where
+        V: PartialEq,
+    {
+        let keep: Vec<bool> = (0..self.vals.len())
+            .map(|i| {
+                let (clock, val) = &self.vals[i];
+                let dominated = self.vals.iter().any(|(c, _)| clock < c);
+                let duplicate = self.vals[..i].iter().any(|(c, v)| clock == c && v == val);
+                !dominated && !duplicate
+            })
+            .collect();
+        let mut keep = keep.into_iter();
+        self.vals.retain(|_| keep.next().unwrap());
+    }
+
+    /// Locally remove all values from the register.
+    ///
+    /// This does not produce an Op and is not a converging operation: the purge is not
+    /// replicated, and a later merge with a peer that still holds the values will
+    /// bring them back. Use this when data must be dropped from this replica only.
+    pub fn purge(&mut self) {
+        self.vals.clear();
+        self.refresh_clock();
+    }
+
+    /// Locally drop the concurrent values that don't satisfy `pred`.
+    ///
+    /// Like `purge`, this does not produce an Op: the dropped values are not
+    /// removed from other replicas and may come back on a later merge.
+    pub fn retain_values(&mut self, pred: impl Fn(&V) -> bool) {
+        self.vals.retain(|(_, val)| pred(val));
+        self.refresh_clock();
+    }
+
     /// Consumes the register and returns the values
     pub fn read(&self) -> ReadCtx<Vec<V>, A>
     where
@@ -193,6 +864,318 @@ impl<V, A: Actor> MVReg<V, A> {
         }
     }
 
+    /// Like `read`, but each value is returned along with the clock it was written at
+    pub fn read_with_clocks(&self) -> ReadCtx<Vec<(VClock<A>, V)>, A>
+    where
+        V: Clone,
+    {
+        let clock = self.clock();
+
+        ReadCtx {
+            add_clock: clock.clone(),
+            rm_clock: clock,
+            val: self.vals.clone(),
+        }
+    }
+
+    /// Like `read`, but borrows the values instead of cloning them into a `Vec`
+    pub fn read_iter(&self) -> ReadCtx<impl Iterator<Item = &V>, A> {
+        let clock = self.clock();
+
+        ReadCtx {
+            add_clock: clock.clone(),
+            rm_clock: clock,
+            val: self.vals.iter().map(|(_, v)| v),
+        }
+    }
+
+    /// Like `read_with_clocks`, but borrows the values instead of cloning them
+    pub fn conflicts(&self) -> ReadCtx<Vec<(VClock<A>, &V)>, A> {
+        let clock = self.clock();
+
+        ReadCtx {
+            add_clock: clock.clone(),
+            rm_clock: clock,
+            val: self.vals.iter().map(|(c, v)| (c.clone(), v)).collect(),
+        }
+    }
+
+    /// The number of concurrent values held by the register
+    pub fn value_count(&self) -> usize {
+        self.vals.len()
+    }
+
+    /// Returns `true` if the register holds more than one concurrent value
+    pub fn is_conflicted(&self) -> bool {
+        self.vals.len() > 1
+    }
+
+    /// Describe where each value of the register came from, e.g. to render a conflict.
+    ///
+    /// Every value is listed with the actors and counters of the clock it was written at,
+    /// in the order the values are stored in.
+    pub fn explain(&self) -> Vec<BranchInfo<'_, V, A>> {
+        self.vals
+            .iter()
+            .enumerate()
+            .map(|(i, (clock, value))| BranchInfo {
+                value,
+                actors: clock.dots().map(|dot| (dot.actor, dot.counter)).collect(),
+                concurrent: self
+                    .vals
+                    .iter()
+                    .enumerate()
+                    .any(|(j, (other, _))| i != j && clock.concurrent(other)),
+            })
+            .collect()
+    }
+
+    /// Like `read`, but folds the concurrent values into one with `resolve`.
+    ///
+    /// The context is the same as `read`'s, so a write derived from it dominates every
+    /// stored value. An empty register reads as `None`.
+    pub fn read_resolved<F: Fn(&V, &V) -> V>(&self, resolve: F) -> ReadCtx<Option<V>, A>
+    where
+        V: Clone,
+    {
+        let mut vals = self.vals.iter().map(|(_, v)| v);
+        let resolved = vals
+            .next()
+            .map(|first| vals.fold(first.clone(), |acc, v| resolve(&acc, v)));
+        let clock = self.clock();
+
+        ReadCtx {
+            add_clock: clock.clone(),
+            rm_clock: clock,
+            val: resolved,
+        }
+    }
+
+    /// Like `read_resolved`, resolving concurrent values to the greatest one
+    pub fn read_latest(&self) -> ReadCtx<Option<V>, A>
+    where
+        V: Clone + Ord,
+    {
+        self.read_resolved(|a, b| cmp::max(a, b).clone())
+    }
+
+    /// Returns the concurrent value with the most recent timestamp.
+    ///
+    /// Values with equal timestamps are tie-broken by comparing their clocks actor by
+    /// actor, so every replica holding the same values picks the same one.
+    pub fn latest_by_time(&self) -> Option<&V>
+    where
+        V: Timestamped,
+    {
+        self.vals
+            .iter()
+            .max_by(|(a_clock, a), (b_clock, b)| {
+                a.timestamp().cmp(&b.timestamp()).then_with(|| {
+                    let a_dots = a_clock.iter().map(|dot| (dot.actor, dot.counter));
+                    let b_dots = b_clock.iter().map(|dot| (dot.actor, dot.counter));
+                    a_dots.cmp(b_dots)
+                })
+            })
+            .map(|(_, val)| val)
+    }
+
+    /// Like `read`, but resolves concurrent values last-writer-wins style to the value
+    /// whose clock holds the highest actor.
+    ///
+    /// Clocks holding the same highest actor are compared by its counter, then by the
+    /// next highest actor and so on, so every replica holding the same values picks the
+    /// same one. The register keeps all the values, this only affects what is read.
+    pub fn read_lww(&self) -> ReadCtx<Option<V>, A>
+    where
+        V: Clone,
+    {
+        let clock = self.clock();
+        let winner = self
+            .vals
+            .iter()
+            .max_by(|(a_clock, _), (b_clock, _)| {
+                a_clock.dots.iter().rev().cmp(b_clock.dots.iter().rev())
+            })
+            .map(|(_, val)| val.clone());
+
+        ReadCtx {
+            add_clock: clock.clone(),
+            rm_clock: clock,
+            val: winner,
+        }
+    }
+
+    /// Apply an op, returning the concurrent values the register held before it.
+    ///
+    /// This is for event sourced projections that emit a change event per op.
+    pub fn apply_returning(&mut self, op: &Op<V, A>) -> Vec<V>
+    where
+        V: Clone + PartialEq,
+    {
+        let previous = self.vals.iter().map(|(_, val)| val.clone()).collect();
+        self.apply(op.clone());
+        previous
+    }
+
+    /// Merge a borrowed register into this one.
+    ///
+    /// `CvRDT::merge` moves values out of an owned register. This is for callers that only
+    /// hold a reference: values of `other` are only cloned if they survive the merge.
+    pub fn merge_ref(&mut self, other: &Self)
+    where
+        V: Clone + PartialEq,
+    {
+        self.vals
+            .retain(|(clock, _)| !other.vals.iter().any(|(c, _)| clock < c));
+
+        let surviving: Vec<_> = other
+            .vals
+            .iter()
+            .filter(|(clock, val)| {
+                !self
+                    .vals
+                    .iter()
+                    .any(|(c, v)| clock < c || (clock == c && v == val))
+            })
+            .cloned()
+            .collect();
+        self.vals.extend(surviving);
+        self.clock.merge_ref(other.clock.dots.iter());
+
+        self.forgotten.merge_ref(other.forgotten.dots.iter());
+        self.drop_forgotten();
+    }
+
+    /// Merge another register into this one, then collapse any concurrent values
+    /// into a single value using the given resolver.
+    ///
+    /// The resolved value is stored under the union of the concurrent clocks, so it
+    /// dominates every value it replaced. Note that this gives up multi-value semantics:
+    /// callers get an auto-resolving register, e.g. last-writer-wins.
+    ///
+    /// The concurrent values are folded in the order of their clocks, compared actor by
+    /// actor, rather than in the order they are stored in, so merging in either direction
+    /// resolves to the same value even if `resolve` isn't commutative.
+    pub fn merge_resolving(&mut self, other: &Self, resolve: impl Fn(&V, &V) -> V)
+    where
+        V: Clone + PartialEq,
+    {
+        self.merge_ref(other);
+        if self.vals.len() > 1 {
+            self.vals
+                .sort_by(|(a_clock, _), (b_clock, _)| a_clock.dots.iter().cmp(b_clock.dots.iter()));
+            let mut vals = mem::take(&mut self.vals).into_iter();
+            let (mut clock, mut val) = vals.next().unwrap();
+            let mut discarded = vec![val.clone()];
+            for (other_clock, other_val) in vals {
+                clock.merge(other_clock);
+                val = resolve(&val, &other_val);
+                discarded.push(other_val);
+            }
+            self.log_resolution(&clock, &val, discarded);
+            self.vals.push((clock, val));
+        }
+    }
+
+    /// Merge many registers into this one at once.
+    ///
+    /// This is equivalent to merging each register in turn, but we only
+    /// filter out dominated values once, after all values have been collected.
+    pub fn merge_all(&mut self, others: &[Self])
+    where
+        V: Clone + PartialEq,
+    {
+        let mut candidates = mem::take(&mut self.vals);
+        candidates.extend(others.iter().flat_map(|other| other.vals.iter().cloned()));
+
+        for (i, (clock, val)) in candidates.iter().enumerate() {
+            let dominated = candidates.iter().any(|(c, _)| clock < c);
+            let seen = candidates[..i].iter().any(|(c, v)| clock == c && v == val);
+            if !dominated && !seen {
+                self.vals.push((clock.clone(), val.clone()));
+            }
+        }
+
+        for other in others {
+            self.forgotten.merge_ref(other.forgotten.dots.iter());
+        }
+        self.drop_forgotten();
+        self.refresh_clock();
+    }
+
+    /// The clock to remove this register with, covering every value it currently holds
+    pub fn rm_clock(&self) -> VClock<A> {
+        self.clock()
+    }
+
+    /// Remove the values whose history is covered by `clock`, typically one produced
+    /// by `rm_clock` on some replica.
+    ///
+    /// Values written concurrently with the remove are not covered by its clock and
+    /// survive untouched, so a concurrent write wins over a remove. Unlike `forget`,
+    /// the clocks of surviving values are not trimmed.
+    pub fn apply_rm(&mut self, clock: &VClock<A>) {
+        self.forgotten.merge(clock.clone());
+        self.vals.retain(|(val_clock, _)| {
+            !matches!(
+                val_clock.partial_cmp(clock),
+                Some(Ordering::Less) | Some(Ordering::Equal)
+            )
+        });
+        self.refresh_clock();
+    }
+
+    /// The version of the values' schema this register was last migrated to, `0` for
+    /// registers that have never been migrated.
+    pub fn schema_version(&self) -> u32 {
+        self.schema_version
+    }
+
+    /// Transform every value, keeping their clocks, and therefore any conflicts, intact.
+    pub fn map_values<W: Clone>(self, mut f: impl FnMut(V) -> W) -> MVReg<W, A> {
+        MVReg {
+            vals: self
+                .vals
+                .into_iter()
+                .map(|(clock, val)| (clock, f(val)))
+                .collect(),
+            forgotten: self.forgotten,
+            clock: self.clock,
+            schema_version: self.schema_version,
+            resolutions: self.resolutions.map(|log| ResolutionLog {
+                entries: log
+                    .entries
+                    .into_iter()
+                    .map(|r| Resolution {
+                        clock: r.clock,
+                        chosen: f(r.chosen),
+                        discarded: r.discarded.into_iter().map(&mut f).collect(),
+                    })
+                    .collect(),
+                copy: W::clone,
+            }),
+            actor: PhantomData,
+        }
+    }
+
+    /// Upgrade the values to a new schema version with `f`.
+    ///
+    /// The register is returned untouched, as `Err`, if it is already at `version` or
+    /// newer. Replicas should migrate before merging or applying ops from one another,
+    /// since the schema version is not reconciled by `merge`.
+    pub fn migrate<W: Clone>(
+        self,
+        version: u32,
+        f: impl FnMut(V) -> W,
+    ) -> Result<MVReg<W, A>, Self> {
+        if self.schema_version >= version {
+            return Err(self);
+        }
+        let mut migrated = self.map_values(f);
+        migrated.schema_version = version;
+        Ok(migrated)
+    }
+
     /// Retrieve the current read context
     pub fn read_ctx(&self) -> ReadCtx<(), A> {
         let clock = self.clock();
@@ -205,11 +1188,107 @@ impl<V, A: Actor> MVReg<V, A> {
 
     /// A clock with latest versions of all actors operating on this register
     fn clock(&self) -> VClock<A> {
-        self.vals
-            .iter()
-            .fold(VClock::new(), |mut accum_clock, (c, _)| {
-                accum_clock.merge(c.clone());
-                accum_clock
-            })
+        self.clock.clone()
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod test {
+    use super::*;
+    use quickcheck::quickcheck;
+
+    fn assert_clock_is_fresh(reg: &MVReg<u8, u8>) {
+        let mut fresh = reg.clone();
+        fresh.refresh_clock();
+        assert_eq!(reg.clock, fresh.clock);
+    }
+
+    #[test]
+    fn test_cached_clock_tracks_mutations() {
+        let mut reg = MVReg::new();
+        let mut peer = MVReg::new();
+        assert_clock_is_fresh(&reg);
+
+        reg.apply(reg.write(1, reg.read().derive_add_ctx(1)));
+        assert_clock_is_fresh(&reg);
+
+        peer.apply(peer.write(2, peer.read().derive_add_ctx(2)));
+        reg.merge(peer.clone());
+        assert_clock_is_fresh(&reg);
+
+        reg.apply(reg.write(3, reg.read().derive_add_ctx(1)));
+        assert_clock_is_fresh(&reg);
+
+        peer.apply(peer.write(4, peer.read().derive_add_ctx(2)));
+        reg.merge_all(&[peer.clone()]);
+        assert_clock_is_fresh(&reg);
+
+        reg.retain_values(|val| *val != 3);
+        assert_clock_is_fresh(&reg);
+
+        reg.forget(&Dot::new(2, 2).into());
+        assert_clock_is_fresh(&reg);
+
+        reg.merge(peer);
+        reg.purge();
+        assert_clock_is_fresh(&reg);
+    }
+
+    #[test]
+    fn test_compact_restores_antichain() {
+        let clock = |dots: &[(u8, u64)]| -> VClock<u8> {
+            dots.iter().map(|(a, c)| Dot::new(*a, *c)).collect()
+        };
+        let mut reg = MVReg::from(State {
+            vals: vec![
+                (clock(&[(1, 1)]), 1),
+                (clock(&[(1, 2)]), 2),
+                (clock(&[(2, 1)]), 3),
+                (clock(&[(1, 2)]), 2),
+                (clock(&[(1, 1), (2, 2)]), 4),
+            ],
+            forgotten: VClock::new(),
+            schema_version: 0,
+        });
+
+        reg.compact();
+        assert_eq!(reg.read().val, vec![2, 4]);
+        assert_clock_is_fresh(&reg);
+    }
+
+    #[test]
+    fn test_cached_clock_is_rebuilt_on_deserialize() {
+        let mut reg: MVReg<u8, u8> = MVReg::new();
+        reg.apply(reg.write(1, reg.read().derive_add_ctx(1)));
+
+        let bytes = bincode::serialize(&reg).unwrap();
+        let decoded: MVReg<u8, u8> = bincode::deserialize(&bytes).unwrap();
+
+        assert_eq!(decoded.clock, reg.clock);
+        assert_eq!(decoded.read().add_clock, reg.read().add_clock);
+    }
+
+    fn build_reg(writes: Vec<(u8, u8)>) -> MVReg<u8, u8> {
+        let mut reg = MVReg::new();
+        for (actor, val) in writes {
+            reg.apply(reg.write(val, reg.read_ctx().derive_add_ctx(actor % 4)));
+        }
+        reg
+    }
+
+    quickcheck! {
+        fn prop_read_clock_matches_value_clocks(
+            writes: Vec<(u8, u8)>,
+            peer_writes: Vec<(u8, u8)>
+        ) -> bool {
+            let mut reg = build_reg(writes);
+            reg.merge(build_reg(peer_writes));
+
+            let folded = reg.vals.iter().fold(reg.forgotten.clone(), |mut clock, (c, _)| {
+                clock.merge(c.clone());
+                clock
+            });
+            reg.read().add_clock == reg.clock() && reg.clock() == folded
+        }
     }
 }
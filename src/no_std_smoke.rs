@@ -0,0 +1,40 @@
+//! Exercises the `no_std` core so that `cargo build --no-default-features` catches
+//! regressions, e.g. an import of something only `std` provides. Nothing here runs.
+#![allow(dead_code)]
+
+use alloc::string::String;
+use core::fmt::Write;
+
+use crate::lseq::LSeq;
+use crate::{CmRDT, CvRDT, Dot, MVReg, VClock};
+
+fn vclock() -> VClock<u8> {
+    let mut clock: VClock<u8> = Dot::new(1, 2).into();
+    clock.apply(clock.inc(2));
+    clock.merge(Dot::new(3, 1).into());
+    clock.glb(&Dot::new(1, 1).into());
+    clock
+}
+
+fn mvreg() -> MVReg<u32, u8> {
+    let mut reg = MVReg::new();
+    let op = reg.write(7, reg.read_ctx().derive_add_ctx(1));
+    reg.apply(op);
+    reg.merge(MVReg::new());
+    reg
+}
+
+fn lseq() -> LSeq<char, u8> {
+    let mut seq = LSeq::new(1);
+    seq.append('a');
+    let op = seq.insert_index(0, 'b');
+    seq.apply(op);
+    seq.delete_index(1);
+    seq
+}
+
+fn display() -> String {
+    let mut out = String::new();
+    let _ = write!(out, "{} {} {}", vclock(), mvreg(), lseq());
+    out
+}
@@ -1,6 +1,7 @@
-/// Observed-Remove Set With Out Tombstones (ORSWOT), ported directly from `riak_dt`.
 use std::cmp::Ordering;
 use std::collections::{HashMap, HashSet};
+/// Observed-Remove Set With Out Tombstones (ORSWOT), ported directly from `riak_dt`.
+use std::convert::Infallible;
 use std::hash::Hash;
 use std::mem;
 
@@ -53,6 +54,7 @@ impl<M: Member, A: Actor> Default for Orswot<M, A> {
 
 impl<M: Member, A: Actor> CmRDT for Orswot<M, A> {
     type Op = Op<M, A>;
+    type Validation = Infallible;
 
     fn apply(&mut self, op: Self::Op) {
         match op {
@@ -80,7 +82,7 @@ impl<M: Member, A: Actor> CmRDT for Orswot<M, A> {
 impl<M: Member, A: Actor> CvRDT for Orswot<M, A> {
     /// Merge combines another `Orswot` with this one.
     fn merge(&mut self, other: Self) {
-        self.entries = mem::replace(&mut self.entries, HashMap::new())
+        self.entries = mem::take(&mut self.entries)
             .into_iter()
             .filter_map(|(entry, mut clock)| {
                 if !other.entries.contains_key(&entry) {
@@ -109,7 +111,7 @@ impl<M: Member, A: Actor> CvRDT for Orswot<M, A> {
                 // SUBTLE: this entry is present in both orswots, BUT that doesn't mean we
                 // shouldn't drop it!
                 // Perfectly possible that an item in both sets should be dropped
-                let mut common = VClock::intersection(&clock, &our_clock);
+                let mut common = VClock::intersection(&clock, our_clock);
                 common.merge(clock.clone_without(&self.clock));
                 common.merge(our_clock.clone_without(&other.clock));
                 if common.is_empty() {
@@ -149,14 +151,14 @@ impl<M: Member, A: Actor> CvRDT for Orswot<M, A> {
 
 impl<M: Member, A: Actor> Causal<A> for Orswot<M, A> {
     fn forget(&mut self, clock: &VClock<A>) {
-        self.clock.forget(&clock);
+        self.clock.forget(clock);
 
         self.entries = self
             .entries
             .clone()
             .into_iter()
             .filter_map(|(val, mut val_clock)| {
-                val_clock.forget(&clock);
+                val_clock.forget(clock);
                 if val_clock.is_empty() {
                     None
                 } else {
@@ -170,7 +172,7 @@ impl<M: Member, A: Actor> Causal<A> for Orswot<M, A> {
             .clone()
             .into_iter()
             .filter_map(|(mut vclock, deferred)| {
-                vclock.forget(&clock);
+                vclock.forget(clock);
                 if vclock.is_empty() {
                     None
                 } else {
@@ -231,10 +233,10 @@ impl<M: Member, A: Actor> Orswot<M, A> {
     /// Remove members using a witnessing clock.
     fn apply_rm(&mut self, members: HashSet<M>, clock: VClock<A>) {
         for member in members.iter() {
-            if let Some(member_clock) = self.entries.get_mut(&member) {
+            if let Some(member_clock) = self.entries.get_mut(member) {
                 member_clock.forget(&clock);
                 if member_clock.is_empty() {
-                    self.entries.remove(&member);
+                    self.entries.remove(member);
                 }
             }
         }
@@ -253,7 +255,7 @@ impl<M: Member, A: Actor> Orswot<M, A> {
 
     /// Check if the set contains a member
     pub fn contains(&self, member: &M) -> ReadCtx<bool, A> {
-        let member_clock_opt = self.entries.get(&member);
+        let member_clock_opt = self.entries.get(member);
         let exists = member_clock_opt.is_some();
         ReadCtx {
             add_clock: self.clock.clone(),
@@ -281,7 +283,7 @@ impl<M: Member, A: Actor> Orswot<M, A> {
     }
 
     fn apply_deferred(&mut self) {
-        let deferred = mem::replace(&mut self.deferred, HashMap::new());
+        let deferred = mem::take(&mut self.deferred);
         for (clock, entries) in deferred.into_iter() {
             self.apply_rm(entries, clock)
         }
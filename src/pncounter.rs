@@ -1,7 +1,10 @@
+use std::convert::Infallible;
+
 use num_bigint::BigInt;
 use serde::{Deserialize, Serialize};
 
-use crate::traits::{Causal, CmRDT, CvRDT};
+use crate::quickcheck::{Arbitrary, Gen};
+use crate::traits::{Causal, CmRDT, CvRDT, DeltaCmRDT};
 use crate::{Actor, Dot, GCounter, VClock};
 
 /// `PNCounter` allows the counter to be both incremented and decremented
@@ -57,6 +60,7 @@ impl<A: Actor> Default for PNCounter<A> {
 
 impl<A: Actor> CmRDT for PNCounter<A> {
     type Op = Op<A>;
+    type Validation = Infallible;
 
     fn apply(&mut self, op: Self::Op) {
         match op {
@@ -73,10 +77,29 @@ impl<A: Actor> CvRDT for PNCounter<A> {
     }
 }
 
+impl<A: Actor> DeltaCmRDT<A> for PNCounter<A> {
+    type Delta = Self;
+
+    /// The delta holds the increments and decrements of every actor that has advanced
+    /// past `since`. Since increments and decrements are counted separately, `since`
+    /// must not be ahead of either of the peer's internal counters: peers should send
+    /// their `frontier`.
+    fn delta(&self, since: &VClock<A>) -> Self {
+        Self {
+            p: self.p.delta(since),
+            n: self.n.delta(since),
+        }
+    }
+
+    fn merge_delta(&mut self, delta: Self) {
+        self.merge(delta);
+    }
+}
+
 impl<A: Actor> Causal<A> for PNCounter<A> {
     fn forget(&mut self, clock: &VClock<A>) {
-        self.p.forget(&clock);
-        self.n.forget(&clock);
+        self.p.forget(clock);
+        self.n.forget(clock);
     }
 }
 
@@ -105,6 +128,15 @@ impl<A: Actor> PNCounter<A> {
         }
     }
 
+    /// The clock a peer should pass to `delta` to catch up with this counter.
+    ///
+    /// This is the per actor minimum of the increment and decrement counters, so it is
+    /// never ahead of either of them. An actor whose decrements lag its increments is
+    /// sent in full, which is safe since merging is idempotent.
+    pub fn frontier(&self) -> VClock<A> {
+        self.p.inner.meet(&self.n.inner)
+    }
+
     /// Return the current value of this counter (P-N).
     pub fn read(&self) -> BigInt {
         let p: BigInt = self.p.read().into();
@@ -113,6 +145,44 @@ impl<A: Actor> PNCounter<A> {
     }
 }
 
+impl<A: Actor + Arbitrary> Arbitrary for PNCounter<A> {
+    fn arbitrary<G: Gen>(g: &mut G) -> Self {
+        Self {
+            p: GCounter::arbitrary(g),
+            n: GCounter::arbitrary(g),
+        }
+    }
+
+    fn shrink(&self) -> Box<dyn Iterator<Item = Self>> {
+        let n = self.n.clone();
+        let shrunk_p = self.p.shrink().map(move |p| Self { p, n: n.clone() });
+        let p = self.p.clone();
+        let shrunk_n = self.n.shrink().map(move |n| Self { p: p.clone(), n });
+        Box::new(shrunk_p.chain(shrunk_n))
+    }
+}
+
+impl<A: Actor + Arbitrary> Arbitrary for Op<A> {
+    fn arbitrary<G: Gen>(g: &mut G) -> Self {
+        Op {
+            dot: Dot::arbitrary(g),
+            dir: if bool::arbitrary(g) {
+                Dir::Pos
+            } else {
+                Dir::Neg
+            },
+        }
+    }
+
+    fn shrink(&self) -> Box<dyn Iterator<Item = Self>> {
+        let dir = self.dir.clone();
+        Box::new(self.dot.shrink().map(move |dot| Op {
+            dot,
+            dir: dir.clone(),
+        }))
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -153,12 +223,6 @@ mod test {
                 }
 
                 results.insert(merged.read());
-                if results.len() > 1 {
-                    println!("opvec: {:?}", ops);
-                    println!("results: {:?}", results);
-                    println!("witnesses: {:?}", &witnesses);
-                    println!("merged: {:?}", merged);
-                }
             }
             results.len() == 1
         }
@@ -181,4 +245,96 @@ mod test {
         a.apply(a.inc("A"));
         assert_eq!(a.read(), 2.into());
     }
+
+    #[test]
+    fn test_concurrent_inc_dec_converge() {
+        let mut a = PNCounter::new();
+        let mut b = PNCounter::new();
+        let mut a_ops = Vec::new();
+        let mut b_ops = Vec::new();
+
+        for inc in [true, true, false].iter() {
+            let op = if *inc { a.inc("A") } else { a.dec("A") };
+            a.apply(op.clone());
+            a_ops.push(op);
+        }
+        for inc in [false, false, true].iter() {
+            let op = if *inc { b.inc("B") } else { b.dec("B") };
+            b.apply(op.clone());
+            b_ops.push(op);
+        }
+
+        let mut a_merged = a.clone();
+        a_merged.merge(b.clone());
+        b_ops.into_iter().for_each(|op| a.apply(op));
+        a_ops.into_iter().for_each(|op| b.apply(op));
+
+        assert_eq!(a, b);
+        assert_eq!(a, a_merged);
+        assert_eq!(a.read(), 0.into());
+    }
+
+    #[test]
+    fn test_delta() {
+        let mut a = PNCounter::new();
+        a.apply(a.inc("A"));
+        a.apply(a.dec("A"));
+        a.apply(a.inc("B"));
+
+        let mut peer = a.clone();
+        // A has been seen by both of the peer's counters, B only by one of them
+        let peer_clock = VClock::from(Dot::new("A", 1));
+
+        a.apply(a.inc("A"));
+        a.apply(a.dec("A"));
+        a.apply(a.inc("A"));
+        a.apply(a.dec("B"));
+
+        let delta = a.delta(&peer_clock);
+        assert_eq!(
+            delta.p.inner,
+            vec![Dot::new("A", 3), Dot::new("B", 1)]
+                .into_iter()
+                .collect()
+        );
+        assert_eq!(
+            delta.n.inner,
+            vec![Dot::new("A", 2), Dot::new("B", 1)]
+                .into_iter()
+                .collect()
+        );
+
+        peer.merge_delta(delta);
+        assert_eq!(peer.read(), 1.into());
+        assert_eq!(peer, a);
+    }
+
+    #[test]
+    fn test_delta_since_frontier_of_peer_with_diverging_counters() {
+        let mut a = PNCounter::new();
+        for _ in 0..3 {
+            a.apply(a.inc("A"));
+        }
+        a.apply(a.dec("A"));
+        a.apply(a.inc("B"));
+
+        // the peer has seen more of A's increments than of its decrements
+        let mut peer = a.clone();
+        a.apply(a.dec("A"));
+        a.apply(a.dec("B"));
+
+        let frontier = peer.frontier();
+        assert_eq!(frontier, VClock::from(Dot::new("A", 1)));
+
+        // the join of the peer's counters is ahead of its decrements and misses one
+        let mut joined = peer.p.inner.clone();
+        joined.merge(peer.n.inner.clone());
+        let mut lossy = peer.clone();
+        lossy.merge_delta(a.delta(&joined));
+        assert_ne!(lossy, a);
+
+        peer.merge_delta(a.delta(&frontier));
+        assert_eq!(peer, a);
+        assert_eq!(peer.read(), 1.into());
+    }
 }
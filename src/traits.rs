@@ -1,6 +1,6 @@
-use std::hash::Hash;
+use core::hash::Hash;
 
-use crate::VClock;
+use crate::{Dot, VClock};
 
 /// Common Actor type. Actors are unique identifier for every `thing` mutating a VClock.
 /// VClock based CRDT's will need to expose this Actor type to the user.
@@ -11,6 +11,22 @@ impl<A: Ord + Clone + Hash> Actor for A {}
 pub trait CvRDT {
     /// Merge the given CRDT into the current CRDT.
     fn merge(&mut self, other: Self);
+
+    /// The identity of the merge lattice, merging with bottom is a no-op.
+    fn bottom() -> Self
+    where
+        Self: Sized + Default,
+    {
+        Self::default()
+    }
+
+    /// Returns true if this CRDT is the bottom of the merge lattice.
+    fn is_bottom(&self) -> bool
+    where
+        Self: Sized + Default + PartialEq,
+    {
+        self == &Self::bottom()
+    }
 }
 
 /// Operation based CRDT's replicate by transmitting each operation.
@@ -38,16 +54,115 @@ pub trait CmRDT {
     /// Op's must be idempotent, meaning any Op may be applied more than once.
     type Op;
 
+    /// The error returned by `validate_op` for ops that are not safe to apply,
+    /// CRDT's that accept every op use `std::convert::Infallible`.
+    type Validation;
+
+    /// Check an op against the current state before applying it.
+    ///
+    /// `apply` stays lenient, this lets applications surface malformed ops
+    /// instead of applying them.
+    fn validate_op(&self, _op: &Self::Op) -> Result<(), Self::Validation> {
+        Ok(())
+    }
+
     /// Apply an Op to the CRDT
     fn apply(&mut self, op: Self::Op);
+
+    /// Apply a batch of ops, all or nothing.
+    ///
+    /// Each op is validated against the state left by the ops before it in the batch.
+    /// On the first op that fails validation, its index and the reason are returned and
+    /// `self` is left unchanged. The default stages the batch on a clone, CRDT's that
+    /// can check a batch up front override it to skip the clone.
+    fn apply_all(&mut self, ops: &[Self::Op]) -> Result<(), (usize, Self::Validation)>
+    where
+        Self: Sized + Clone,
+        Self::Op: Clone,
+    {
+        let mut staged = self.clone();
+        for (i, op) in ops.iter().enumerate() {
+            staged.validate_op(op).map_err(|err| (i, err))?;
+            staged.apply(op.clone());
+        }
+        *self = staged;
+        Ok(())
+    }
+}
+
+/// Op based CRDT's whose ops are each witnessed by a single dot.
+///
+/// The dot uniquely identifies the op, so it can be used as a deduplication key
+/// for exactly-once delivery. This is not implemented for CRDT's whose ops carry
+/// a whole clock (e.g. `MVReg` puts or `Orswot` removes), as no single dot
+/// identifies them.
+pub trait OpId<A: Actor>: CmRDT {
+    /// The dot that uniquely identifies this op
+    fn op_id(op: &Self::Op) -> Dot<A>;
+}
+
+/// Delta based CRDT's replicate by transmitting only the part of their state
+/// that a peer has not yet seen.
+///
+/// Merging `self.delta(&peer_clock)` into a peer whose state is described by
+/// `peer_clock` should have the same effect as merging all of `self`.
+pub trait DeltaCmRDT<A: Actor> {
+    /// The state fragment produced by `delta` and consumed by `merge_delta`
+    type Delta;
+
+    /// Extract the state that is not already described by `since`
+    fn delta(&self, since: &VClock<A>) -> Self::Delta;
+
+    /// Merge a delta produced by a peer into this CRDT
+    fn merge_delta(&mut self, delta: Self::Delta);
+}
+
+/// CRDT's whose state can be captured as plain data and restored from it.
+///
+/// The captured state only depends on the replicated data, not on how a CRDT lays out
+/// its fields, so it is the form to persist: `State` is serializable whenever the
+/// CRDT's values are, and keeps its format as internal fields change.
+pub trait Snapshot {
+    /// The plain data form of this CRDT's state
+    type State;
+
+    /// Capture the state of this CRDT
+    fn snapshot(&self) -> Self::State;
+
+    /// Rebuild a CRDT from a captured state, `restore(crdt.snapshot())` equals `crdt`
+    fn restore(state: Self::State) -> Self;
 }
 
 /// CRDT's are causal if they are built on top of vector clocks.
+///
+/// CRDT's with no causal metadata to compact can implement this with an empty
+/// impl block, so that they can still be driven by generic compaction code.
 pub trait Causal<A: Actor> {
-    /// Forget data that is strictly smaller than this clock
-    fn forget(&mut self, clock: &VClock<A>);
+    /// Forget data that is strictly smaller than this clock, by default nothing is forgotten
+    fn forget(&mut self, _clock: &VClock<A>) {}
 }
 
+/// The causal context a CRDT tracks the history of its values with, e.g. a `VClock`.
+///
+/// Contexts are partially ordered by causality: `a < b` when `b` has seen everything
+/// `a` has seen and more. Alternatives such as hybrid logical clocks can be used
+/// wherever a CRDT is generic over its context.
+pub trait CausalContext: Clone + Default + PartialOrd {
+    /// Merge `other` into this context, the result dominates both
+    fn join(&mut self, other: &Self);
+
+    /// Remove the history `other` has seen from this context
+    fn subtract(&mut self, other: &Self);
+
+    /// True if this context hasn't seen any events
+    fn is_empty(&self) -> bool;
+}
+
+/// Convenience bound for generic code over any vector clock based CRDT that
+/// replicates both by state and by ops.
+pub trait Crdt<A: Actor>: CmRDT + CvRDT + Causal<A> + Clone {}
+impl<A: Actor, T: CmRDT + CvRDT + Causal<A> + Clone> Crdt<A> for T {}
+
 /// Funky variant of the `CvRDT` trait.
 ///
 /// This trait is for CvRDT's whose state space can't be easily encoded in rusts
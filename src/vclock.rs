@@ -12,16 +12,23 @@
 //! assert!(a > b);
 //! ```
 
-use std::cmp::{self, Ordering};
-use std::collections::{btree_map, BTreeMap};
-use std::fmt::{self, Display};
-use std::hash::Hash;
-use std::mem;
-
+use alloc::collections::{btree_map, BTreeMap};
+use alloc::vec::Vec;
+use core::cmp::{self, Ordering};
+use core::convert::{Infallible, TryFrom};
+use core::fmt::{self, Display};
+use core::hash::Hash;
+use core::marker::PhantomData;
+use core::mem;
+#[cfg(feature = "std")]
+use std::{collections::HashSet, error};
+
+use serde::de::{self, Deserializer, MapAccess, Visitor};
 use serde::{Deserialize, Serialize};
 
+#[cfg(feature = "std")]
 use crate::quickcheck::{Arbitrary, Gen};
-use crate::{Actor, Causal, CmRDT, CvRDT, Dot};
+use crate::{Actor, Causal, CausalContext, CmRDT, CvRDT, Dot, OpId};
 
 /// A `VClock` is a standard vector clock.
 /// It contains a set of "actors" and associated counters.
@@ -32,12 +39,60 @@ use crate::{Actor, Causal, CmRDT, CvRDT, Dot};
 /// It can tell you if something causally descends something else,
 /// or if different replicas are "concurrent" (were mutated in
 /// isolation, and need to be resolved externally).
+///
+/// Equality is derived from the `dots` map, which compares the number of actors
+/// before comparing entries, so clocks of different sizes are unequal in O(1).
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct VClock<A: Actor> {
-    /// dots is the mapping from actors to their associated counters
+    /// dots is the mapping from actors to their associated counters.
+    ///
+    /// A `BTreeMap` keeps clocks compact for the usual handful of actors and gives a
+    /// canonical iteration order, so equal clocks hash and encode identically.
+    ///
+    /// Zero counters are dropped on deserialize, since comparisons rely on clocks
+    /// never storing them.
+    #[serde(
+        deserialize_with = "deserialize_nonzero_dots",
+        bound(deserialize = "A: Deserialize<'de>")
+    )]
     pub dots: BTreeMap<A, u64>,
 }
 
+fn deserialize_nonzero_dots<'de, A, D>(deserializer: D) -> Result<BTreeMap<A, u64>, D::Error>
+where
+    A: Actor + Deserialize<'de>,
+    D: Deserializer<'de>,
+{
+    let mut dots = BTreeMap::<A, u64>::deserialize(deserializer)?;
+    dots.retain(|_, counter| *counter > 0);
+    Ok(dots)
+}
+
+/// The causal relationship between two clocks, where `PartialOrd` would give `None`
+/// for concurrent clocks this has an explicit `Concurrent` variant.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CausalOrder {
+    /// The first clock is strictly dominated by the second
+    Less,
+    /// The clocks are equal
+    Equal,
+    /// The first clock strictly dominates the second
+    Greater,
+    /// Neither clock dominates the other
+    Concurrent,
+}
+
+impl From<Option<Ordering>> for CausalOrder {
+    fn from(ordering: Option<Ordering>) -> Self {
+        match ordering {
+            Some(Ordering::Less) => CausalOrder::Less,
+            Some(Ordering::Equal) => CausalOrder::Equal,
+            Some(Ordering::Greater) => CausalOrder::Greater,
+            None => CausalOrder::Concurrent,
+        }
+    }
+}
+
 impl<A: Actor> Default for VClock<A> {
     fn default() -> Self {
         Self::new()
@@ -77,13 +132,40 @@ impl<A: Actor + Display> Display for VClock<A> {
     }
 }
 
+impl<A: Actor> CausalContext for VClock<A> {
+    fn join(&mut self, other: &Self) {
+        self.merge_ref(other.dots.iter());
+    }
+
+    fn subtract(&mut self, other: &Self) {
+        self.forget(other);
+    }
+
+    fn is_empty(&self) -> bool {
+        self.dots.is_empty()
+    }
+}
+
 impl<A: Actor> Causal<A> for VClock<A> {
     /// Forget any actors that have smaller counts than the
     /// count in the given vclock
+    ///
+    /// This leaves only the entries that have advanced beyond `other`, e.g. to find
+    /// what a peer hasn't acked yet.
+    ///
+    /// # Examples
+    /// ```
+    /// use crdts::{Causal, VClock, Dot};
+    /// let mut c: VClock<_> = vec![Dot::new("A", 3), Dot::new("B", 1)].into_iter().collect();
+    /// c.forget(&Dot::new("A", 2).into());
+    /// assert_eq!(c, vec![Dot::new("A", 3), Dot::new("B", 1)].into_iter().collect());
+    /// c.forget(&Dot::new("A", 3).into());
+    /// assert_eq!(c, Dot::new("B", 1).into());
+    /// ```
     fn forget(&mut self, other: &Self) {
         for Dot { actor, counter } in other.iter() {
-            if counter >= self.get(&actor) {
-                self.dots.remove(&actor);
+            if counter >= self.get(actor) {
+                self.dots.remove(actor);
             }
         }
     }
@@ -91,6 +173,7 @@ impl<A: Actor> Causal<A> for VClock<A> {
 
 impl<A: Actor> CmRDT for VClock<A> {
     type Op = Dot<A>;
+    type Validation = Infallible;
 
     /// Monotonically adds the given actor version to
     /// this VClock.
@@ -112,12 +195,38 @@ impl<A: Actor> CmRDT for VClock<A> {
     }
 }
 
+impl<A: Actor> OpId<A> for VClock<A> {
+    fn op_id(dot: &Self::Op) -> Dot<A> {
+        dot.clone()
+    }
+}
+
 impl<A: Actor> CvRDT for VClock<A> {
+    /// Raise every actor's counter to at least its counter in `other`, e.g. to
+    /// fast-forward a replica to a known checkpoint. `merge_ref` does the same from
+    /// borrowed entries.
     fn merge(&mut self, other: Self) {
         for dot in other.into_iter() {
+            // an actor type whose Ord and Eq disagree would have two distinct actors
+            // share a slot, silently mixing up their counters
+            debug_assert!(
+                self.dots
+                    .get_key_value(&dot.actor)
+                    .into_iter()
+                    .all(|(actor, _)| actor == &dot.actor),
+                "distinct actors collided in VClock::merge, the actor's Ord and Eq disagree"
+            );
             self.apply_dot(dot);
         }
     }
+
+    fn bottom() -> Self {
+        Self::new()
+    }
+
+    fn is_bottom(&self) -> bool {
+        self.is_empty()
+    }
 }
 
 impl<A: Actor> VClock<A> {
@@ -129,18 +238,62 @@ impl<A: Actor> VClock<A> {
     }
 
     /// Returns a clone of self but with information that is older than given clock is
-    /// forgotten
+    /// forgotten, i.e. the actors that have seen more than `base_clock`, with their
+    /// counters.
+    ///
+    /// Together with `meet` it splits a clock into the history shared with `base_clock`
+    /// and the history beyond it.
+    /// ``` rust
+    /// use crdts::{VClock, Dot, CvRDT};
+    /// let a: VClock<_> = vec![Dot::new("A", 3), Dot::new("B", 1)].into_iter().collect();
+    /// let b: VClock<_> = vec![Dot::new("A", 2), Dot::new("B", 4)].into_iter().collect();
+    /// assert_eq!(a.clone_without(&b), VClock::from(Dot::new("A", 3)));
+    ///
+    /// let mut rebuilt = a.clone_without(&b);
+    /// rebuilt.merge(a.meet(&b));
+    /// assert_eq!(rebuilt, a);
+    /// ```
+    #[doc(alias = "difference")]
     pub fn clone_without(&self, base_clock: &Self) -> Self {
         let mut cloned = self.clone();
-        cloned.forget(&base_clock);
+        cloned.forget(base_clock);
         cloned
     }
 
+    /// Record that a peer acknowledged everything up to `dot`.
+    ///
+    /// Meant for a clock tracking what has been sent but not yet acknowledged: the
+    /// actor's entry is removed once the ack covers it, so `clone_without` against the
+    /// peer's clock only reports what still needs to be retransmitted.
+    ///
+    /// # Examples
+    /// ```
+    /// use crdts::{VClock, Dot};
+    /// let mut sent = VClock::from(Dot::new("A", 2));
+    /// sent.ack(&Dot::new("A", 1));
+    /// assert_eq!(sent.get(&"A"), 2);
+    /// sent.ack(&Dot::new("A", 2));
+    /// assert!(sent.is_empty());
+    /// ```
+    pub fn ack(&mut self, dot: &Dot<A>) {
+        if self.get(&dot.actor) <= dot.counter {
+            self.dots.remove(&dot.actor);
+        }
+    }
+
     /// Apply a Dot to this vclock.
     fn apply_dot(&mut self, dot: Dot<A>) {
-        if self.get(&dot.actor) < dot.counter {
+        self.apply_dot_returning(dot);
+    }
+
+    /// Apply a Dot to this vclock, returning the actor's counter from before the dot
+    /// was applied. A dot more than one past the returned counter skipped some events.
+    pub fn apply_dot_returning(&mut self, dot: Dot<A>) -> u64 {
+        let previous = self.get(&dot.actor);
+        if previous < dot.counter {
             self.dots.insert(dot.actor, dot.counter);
         }
+        previous
     }
 
     /// Generate Op to increment an actor's counter.
@@ -175,6 +328,11 @@ impl<A: Actor> VClock<A> {
         self.dots.get(actor).cloned().unwrap_or(0)
     }
 
+    /// True if `actor` has a non-zero counter in this clock
+    pub fn contains_actor(&self, actor: &A) -> bool {
+        self.get(actor) > 0
+    }
+
     /// Return the Dot for a given actor
     pub fn dot(&self, actor: A) -> Dot<A> {
         let counter = self.get(&actor);
@@ -195,6 +353,80 @@ impl<A: Actor> VClock<A> {
         self.partial_cmp(other).is_none()
     }
 
+    /// Returns the number of actors whose counter is at least `n`.
+    ///
+    /// # Examples
+    /// ```
+    /// use crdts::{VClock, Dot};
+    /// let c: VClock<_> = vec![Dot::new("A", 3), Dot::new("B", 1)].into_iter().collect();
+    /// assert_eq!(c.actors_at_least(2), 1);
+    /// ```
+    pub fn actors_at_least(&self, n: u64) -> usize {
+        self.dots.values().filter(|counter| **counter >= n).count()
+    }
+
+    /// Returns `true` if an op with dependency clock `dep` and dot `op_dot` can be
+    /// delivered to a replica at this clock: all of its dependencies have been seen
+    /// and it is the very next op from its originating actor.
+    ///
+    /// # Examples
+    /// ```
+    /// use crdts::{VClock, Dot};
+    /// let c: VClock<_> = vec![Dot::new("A", 2), Dot::new("B", 1)].into_iter().collect();
+    /// let dep: VClock<_> = Dot::new("B", 1).into();
+    /// assert!(c.ready_for(&dep, &Dot::new("A", 3)));
+    /// assert!(!c.ready_for(&dep, &Dot::new("A", 4)));
+    /// ```
+    pub fn ready_for(&self, dep: &VClock<A>, op_dot: &Dot<A>) -> bool {
+        dep <= self && self.get(&op_dot.actor).checked_add(1) == Some(op_dot.counter)
+    }
+
+    /// Returns the number of distinct actors across both clocks, i.e. the number of
+    /// actors the merged clock would have, without building the merged clock.
+    ///
+    /// # Examples
+    /// ```
+    /// use crdts::{VClock, Dot};
+    /// let a: VClock<_> = vec![Dot::new("A", 1), Dot::new("B", 2)].into_iter().collect();
+    /// let b: VClock<_> = vec![Dot::new("B", 1), Dot::new("C", 4)].into_iter().collect();
+    /// assert_eq!(a.union_actor_count(&b), 3);
+    /// ```
+    pub fn union_actor_count(&self, other: &Self) -> usize {
+        let only_in_other = other
+            .dots
+            .keys()
+            .filter(|actor| !self.dots.contains_key(actor))
+            .count();
+        self.dots.len() + only_in_other
+    }
+
+    /// Split this clock against a peer's acknowledgement into `(acked, pending)`.
+    ///
+    /// `acked` is the part of this clock the peer has acknowledged, i.e. the `glb` of the
+    /// two clocks. `pending` holds the actors that have advanced beyond the ack, with
+    /// their full counters, as left by `forget`.
+    ///
+    /// # Examples
+    /// ```
+    /// use crdts::{VClock, Dot};
+    /// let c: VClock<_> = vec![Dot::new("A", 5), Dot::new("B", 2)].into_iter().collect();
+    /// let (acked, pending) = c.split_by(&Dot::new("A", 3).into());
+    /// assert_eq!(acked, Dot::new("A", 3).into());
+    /// assert_eq!(pending, c);
+    /// ```
+    pub fn split_by(&self, ack: &VClock<A>) -> (VClock<A>, VClock<A>) {
+        let mut acked = self.clone();
+        acked.glb(ack);
+        let mut pending = self.clone();
+        pending.forget(ack);
+        (acked, pending)
+    }
+
+    /// Remove every actor from this clock.
+    pub fn clear(&mut self) {
+        self.dots.clear();
+    }
+
     /// Returns `true` if this vector clock contains nothing.
     pub fn is_empty(&self) -> bool {
         self.dots.is_empty()
@@ -213,6 +445,25 @@ impl<A: Actor> VClock<A> {
         Self { dots }
     }
 
+    /// Returns the greatest-lower-bound of this VClock and `other`: the
+    /// per-actor minimum of the two counters. This is the non-mutating form
+    /// of [`VClock::glb`], and unlike [`VClock::intersection`] it keeps actors
+    /// whose counters differ.
+    ///
+    /// The meet is the causal history both clocks have observed, e.g. the
+    /// bound below which two replicas can safely `forget` tombstones.
+    /// ``` rust
+    /// use crdts::{VClock, Dot};
+    /// let a: VClock<_> = vec![Dot::new("A", 3), Dot::new("B", 1)].into_iter().collect();
+    /// let b: VClock<_> = vec![Dot::new("A", 2), Dot::new("C", 4)].into_iter().collect();
+    /// assert_eq!(a.meet(&b), VClock::from(Dot::new("A", 2)));
+    /// ```
+    pub fn meet(&self, other: &Self) -> Self {
+        let mut meet = self.clone();
+        meet.glb(other);
+        meet
+    }
+
     /// Reduces this VClock to the greatest-lower-bound of the given
     /// VClock and itsef, as an example see the following code.
     /// ``` rust
@@ -231,7 +482,7 @@ impl<A: Actor> VClock<A> {
     /// assert_eq!(c.get(&43), 0);
     /// ```
     pub fn glb(&mut self, other: &Self) {
-        self.dots = mem::replace(&mut self.dots, BTreeMap::new())
+        self.dots = mem::take(&mut self.dots)
             .into_iter()
             .filter_map(|(actor, count)| {
                 // Since an actor missing from the dots map has an implied
@@ -245,6 +496,96 @@ impl<A: Actor> VClock<A> {
             .collect();
     }
 
+    /// Merge borrowed `(actor, counter)` entries into this clock.
+    ///
+    /// Unlike `merge`, this doesn't need an owned clock: actors are only cloned when they
+    /// are new to this clock, so merging mostly-known entries is cheap.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::collections::BTreeMap;
+    /// use crdts::{VClock, Dot};
+    /// let mut c = VClock::from(Dot::new("A", 3));
+    /// let entries: BTreeMap<_, _> = vec![("A", 5), ("B", 1)].into_iter().collect();
+    /// c.merge_ref(entries.iter());
+    /// assert_eq!(c.get(&"A"), 5);
+    /// assert_eq!(c.get(&"B"), 1);
+    /// ```
+    pub fn merge_ref<'a>(&mut self, entries: impl Iterator<Item = (&'a A, &'a u64)>)
+    where
+        A: 'a,
+    {
+        for (actor, counter) in entries {
+            match self.dots.get_mut(actor) {
+                Some(existing) => *existing = cmp::max(*existing, *counter),
+                None if *counter > 0 => {
+                    self.dots.insert(actor.clone(), *counter);
+                }
+                None => (),
+            }
+        }
+    }
+
+    /// The sum of all counters in the clock, saturating at `u64::MAX`.
+    ///
+    /// This is the value a `GCounter` with these counts would read.
+    pub fn sum(&self) -> u64 {
+        self.dots
+            .values()
+            .fold(0u64, |total, counter| total.saturating_add(*counter))
+    }
+
+    /// Merge `other` into this clock, returning how this clock compared to `other`
+    /// before the merge. Useful for anti-entropy to log whether peers had diverged.
+    ///
+    /// ```
+    /// use crdts::{VClock, Dot, vclock::CausalOrder};
+    /// let mut a = VClock::from(Dot::new("A", 1));
+    /// let b = VClock::from(Dot::new("B", 1));
+    /// assert_eq!(a.merge_reporting(&b), CausalOrder::Concurrent);
+    /// assert_eq!(a.merge_reporting(&b), CausalOrder::Greater);
+    /// ```
+    pub fn merge_reporting(&mut self, other: &Self) -> CausalOrder {
+        let order = CausalOrder::from((*self).partial_cmp(other));
+        self.merge_ref(other.dots.iter());
+        order
+    }
+
+    /// Merge a sequence of dots into this clock, keeping the largest counter seen for each actor.
+    ///
+    /// # Examples
+    /// ```
+    /// use crdts::{VClock, Dot};
+    /// let mut c = VClock::from(Dot::new("A", 3));
+    /// c.merge_dots(vec![Dot::new("A", 2), Dot::new("A", 5), Dot::new("B", 1)]);
+    /// assert_eq!(c.get(&"A"), 5);
+    /// assert_eq!(c.get(&"B"), 1);
+    /// ```
+    pub fn merge_dots(&mut self, dots: impl IntoIterator<Item = Dot<A>>) {
+        for dot in dots {
+            self.apply_dot(dot);
+        }
+    }
+
+    /// Advance this clock to match `other`, but only for the given actors.
+    ///
+    /// # Examples
+    /// ```
+    /// use crdts::{VClock, Dot};
+    /// let mut a: VClock<_> = vec![Dot::new("A", 1), Dot::new("B", 1)].into_iter().collect();
+    /// let b: VClock<_> = vec![Dot::new("A", 3), Dot::new("B", 3)].into_iter().collect();
+    ///
+    /// a.overlay(&b, &vec!["A"].into_iter().collect());
+    /// assert_eq!(a.get(&"A"), 3);
+    /// assert_eq!(a.get(&"B"), 1);
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn overlay(&mut self, other: &Self, actors: &HashSet<A>) {
+        for actor in actors.iter() {
+            self.apply_dot(other.dot(actor.clone()));
+        }
+    }
+
     /// Returns an iterator over the dots in this vclock
     pub fn iter(&self) -> impl Iterator<Item = Dot<&A>> {
         self.dots.iter().map(|(a, c)| Dot {
@@ -252,6 +593,235 @@ impl<A: Actor> VClock<A> {
             counter: *c,
         })
     }
+
+    /// Returns an iterator over owned copies of the dots in this vclock, one per actor in
+    /// ascending actor order.
+    ///
+    /// # Examples
+    /// ```
+    /// use crdts::{VClock, Dot};
+    /// let clock: VClock<_> = vec![Dot::new("B", 2), Dot::new("A", 1)].into_iter().collect();
+    /// assert_eq!(
+    ///     clock.dots().collect::<Vec<_>>(),
+    ///     vec![Dot::new("A", 1), Dot::new("B", 2)]
+    /// );
+    /// ```
+    pub fn dots(&self) -> impl Iterator<Item = Dot<A>> + '_ {
+        self.dots
+            .iter()
+            .map(|(actor, counter)| Dot::new(actor.clone(), *counter))
+    }
+}
+
+impl<A: Actor + Into<u64> + TryFrom<u64>> VClock<A> {
+    /// Encode this clock as a compact sequence of LEB128 varints.
+    ///
+    /// The encoding is the number of actors followed by each
+    /// `(actor, counter)` pair in actor order. Small actor ids and
+    /// counters take a single byte each, which makes this much smaller
+    /// than a general purpose serde encoding for sparse clocks.
+    ///
+    /// # Examples
+    /// ```
+    /// use crdts::{VClock, Dot, CmRDT};
+    /// let mut c: VClock<u64> = VClock::new();
+    /// c.apply(Dot::new(3, 200));
+    ///
+    /// let bytes = c.encode_varint();
+    /// assert_eq!(bytes, vec![1, 3, 200, 1]);
+    /// assert_eq!(VClock::decode_varint(&bytes), Ok(c));
+    /// ```
+    pub fn encode_varint(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        write_varint(&mut bytes, self.dots.len() as u64);
+        for (actor, counter) in self.dots.iter() {
+            write_varint(&mut bytes, actor.clone().into());
+            write_varint(&mut bytes, *counter);
+        }
+        bytes
+    }
+
+    /// Decode a clock produced by [`VClock::encode_varint`].
+    pub fn decode_varint(bytes: &[u8]) -> Result<Self, DecodeError> {
+        let mut cursor = bytes;
+        let len = read_varint(&mut cursor)?;
+
+        let mut clock = VClock::new();
+        for _ in 0..len {
+            let actor =
+                A::try_from(read_varint(&mut cursor)?).map_err(|_| DecodeError::InvalidActor)?;
+            let counter = read_varint(&mut cursor)?;
+            clock.apply(Dot::new(actor, counter));
+        }
+
+        if cursor.is_empty() {
+            Ok(clock)
+        } else {
+            Err(DecodeError::TrailingBytes)
+        }
+    }
+}
+
+impl<'de, A: Actor + Deserialize<'de>> VClock<A> {
+    /// Leniently parse a clock from an external map of actors to counters, where the
+    /// counters may be numbers or strings holding a number, e.g. `{"a": 1, "b": "2"}`.
+    ///
+    /// This works with any self-describing deserializer, e.g. a `serde_json::Value`.
+    /// Negative or otherwise invalid counters are rejected.
+    pub fn from_lenient<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_map(LenientClockVisitor(PhantomData))
+    }
+}
+
+#[cfg(feature = "serde_json")]
+impl<A: Actor + de::DeserializeOwned> VClock<A> {
+    /// Leniently parse a clock from a JSON object of actors to counters, see `from_lenient`.
+    ///
+    /// # Examples
+    /// ```
+    /// use crdts::VClock;
+    /// let value = serde_json::json!({"a": 1, "b": "2"});
+    /// let clock: VClock<String> = VClock::from_json_value(&value).unwrap();
+    /// assert_eq!(clock.get(&"b".to_string()), 2);
+    /// ```
+    pub fn from_json_value(value: &serde_json::Value) -> Result<Self, ParseError> {
+        Self::from_lenient(value).map_err(ParseError)
+    }
+}
+
+struct LenientClockVisitor<A>(PhantomData<A>);
+
+impl<'de, A: Actor + Deserialize<'de>> Visitor<'de> for LenientClockVisitor<A> {
+    type Value = VClock<A>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "a map of actors to counters")
+    }
+
+    fn visit_map<M: MapAccess<'de>>(self, mut map: M) -> Result<Self::Value, M::Error> {
+        let mut clock = VClock::new();
+        while let Some((actor, LenientCounter(counter))) = map.next_entry()? {
+            clock.apply(Dot::new(actor, counter));
+        }
+        Ok(clock)
+    }
+}
+
+/// A counter that may be given as a number or as a string holding a number
+struct LenientCounter(u64);
+
+impl<'de> Deserialize<'de> for LenientCounter {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(LenientCounterVisitor)
+    }
+}
+
+struct LenientCounterVisitor;
+
+impl<'de> Visitor<'de> for LenientCounterVisitor {
+    type Value = LenientCounter;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "a non-negative counter, as a number or a string")
+    }
+
+    fn visit_u64<E: de::Error>(self, counter: u64) -> Result<Self::Value, E> {
+        Ok(LenientCounter(counter))
+    }
+
+    fn visit_i64<E: de::Error>(self, counter: i64) -> Result<Self::Value, E> {
+        u64::try_from(counter)
+            .map(LenientCounter)
+            .map_err(|_| E::invalid_value(de::Unexpected::Signed(counter), &self))
+    }
+
+    fn visit_str<E: de::Error>(self, counter: &str) -> Result<Self::Value, E> {
+        counter
+            .trim()
+            .parse()
+            .map(LenientCounter)
+            .map_err(|_| E::invalid_value(de::Unexpected::Str(counter), &self))
+    }
+}
+
+/// Errors produced when decoding a varint encoded `VClock`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DecodeError {
+    /// The input ended in the middle of the encoding.
+    UnexpectedEnd,
+    /// A varint encoded a number that does not fit in a u64.
+    Overflow,
+    /// An actor id could not be converted back into the actor type.
+    InvalidActor,
+    /// There were bytes left over after decoding the clock.
+    TrailingBytes,
+}
+
+#[cfg(feature = "std")]
+impl error::Error for DecodeError {}
+
+impl Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DecodeError::UnexpectedEnd => write!(f, "unexpected end of varint encoded clock"),
+            DecodeError::Overflow => write!(f, "varint does not fit in a u64"),
+            DecodeError::InvalidActor => write!(f, "actor id is out of range for the actor type"),
+            DecodeError::TrailingBytes => write!(f, "trailing bytes after varint encoded clock"),
+        }
+    }
+}
+
+/// Error produced when a `VClock` can't be parsed from a JSON value.
+#[cfg(feature = "serde_json")]
+#[derive(Debug)]
+pub struct ParseError(serde_json::Error);
+
+#[cfg(all(feature = "serde_json", feature = "std"))]
+impl error::Error for ParseError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        Some(&self.0)
+    }
+}
+
+#[cfg(feature = "serde_json")]
+impl Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid clock: {}", self.0)
+    }
+}
+
+/// Append `n` to `bytes` as an unsigned LEB128 varint.
+fn write_varint(bytes: &mut Vec<u8>, mut n: u64) {
+    loop {
+        let byte = (n & 0x7f) as u8;
+        n >>= 7;
+        if n == 0 {
+            bytes.push(byte);
+            return;
+        }
+        bytes.push(byte | 0x80);
+    }
+}
+
+/// Read an unsigned LEB128 varint from the front of `bytes`, advancing it.
+fn read_varint(bytes: &mut &[u8]) -> Result<u64, DecodeError> {
+    let mut n: u64 = 0;
+    let mut shift = 0;
+    loop {
+        let (&byte, rest) = bytes.split_first().ok_or(DecodeError::UnexpectedEnd)?;
+        *bytes = rest;
+
+        let low_bits = u64::from(byte & 0x7f);
+        if shift >= 64 || (shift == 63 && low_bits > 1) {
+            return Err(DecodeError::Overflow);
+        }
+        n |= low_bits << shift;
+
+        if byte & 0x80 == 0 {
+            return Ok(n);
+        }
+        shift += 7;
+    }
 }
 
 /// Generated from calls to VClock::into_iter()
@@ -259,7 +829,7 @@ pub struct IntoIter<A: Actor> {
     btree_iter: btree_map::IntoIter<A, u64>,
 }
 
-impl<A: Actor> std::iter::Iterator for IntoIter<A> {
+impl<A: Actor> core::iter::Iterator for IntoIter<A> {
     type Item = Dot<A>;
 
     fn next(&mut self) -> Option<Dot<A>> {
@@ -269,7 +839,7 @@ impl<A: Actor> std::iter::Iterator for IntoIter<A> {
     }
 }
 
-impl<A: Actor> std::iter::IntoIterator for VClock<A> {
+impl<A: Actor> core::iter::IntoIterator for VClock<A> {
     type Item = Dot<A>;
     type IntoIter = IntoIter<A>;
 
@@ -281,7 +851,7 @@ impl<A: Actor> std::iter::IntoIterator for VClock<A> {
     }
 }
 
-impl<A: Actor> std::iter::FromIterator<Dot<A>> for VClock<A> {
+impl<A: Actor> core::iter::FromIterator<Dot<A>> for VClock<A> {
     fn from_iter<I: IntoIterator<Item = Dot<A>>>(iter: I) -> Self {
         let mut clock = VClock::new();
 
@@ -301,6 +871,7 @@ impl<A: Actor> From<Dot<A>> for VClock<A> {
     }
 }
 
+#[cfg(feature = "std")]
 impl<A: Actor + Arbitrary> Arbitrary for VClock<A> {
     fn arbitrary<G: Gen>(g: &mut G) -> Self {
         let mut clock = VClock::new();
@@ -1,7 +1,8 @@
-use crdts::lseq::{LSeq, Op};
-use crdts::CmRDT;
+use crdts::lseq::{ident::AllocStrategy, Cursor, LSeq, Op, OpError};
+use crdts::{Causal, CmRDT, DeltaCmRDT, Dot, MVReg, OpId, Snapshot, VClock};
 use rand::distributions::Alphanumeric;
 use rand::Rng;
+use std::cmp::{self, Ordering};
 
 type SiteId = u32;
 #[derive(Debug, Clone)]
@@ -23,8 +24,8 @@ impl Arbitrary for OperationList {
         let mut site1 = LSeq::new(g.gen());
         let ops = (0..size)
             .filter_map(|_| {
-                if g.gen() || site1.len() == 0 {
-                    site1.delete_index(g.gen_range(0, site1.len() + 1))
+                if g.gen() || site1.is_empty() {
+                    Some(site1.insert_index(g.gen_range(0, site1.len() + 1), g.gen()))
                 } else {
                     site1.delete_index(g.gen_range(0, site1.len()))
                 }
@@ -84,25 +85,933 @@ fn test_append_mixed_with_inserts() {
     assert_eq!(site1.iter().collect::<String>(), "bdac");
 }
 
+#[test]
+fn test_insert_sorted() {
+    let mut site1 = LSeq::new(0);
+    let mut site2 = LSeq::new(1);
+    for c in "lseqcrdt".chars() {
+        let op = site1.insert_sorted(c);
+        site2.apply(op);
+    }
+
+    assert_eq!(site1.iter().collect::<String>(), "cdelqrst");
+    assert_eq!(site2.iter().collect::<String>(), "cdelqrst");
+}
+
+#[test]
+fn test_reverse() {
+    let mut site1 = LSeq::new(0);
+    let mut site2 = LSeq::new(1);
+    for i in 1..=3 {
+        site2.apply(site1.append(i));
+    }
+
+    for op in site1.reverse() {
+        site2.apply(op);
+    }
+
+    assert_eq!(site1.iter().collect::<Vec<_>>(), vec![&3, &2, &1]);
+    assert_eq!(site2.iter().collect::<Vec<_>>(), vec![&3, &2, &1]);
+}
+
+#[test]
+fn test_rebalance_converges_with_concurrent_inserts() {
+    let mut site1 = LSeq::new(0);
+    let mut site2 = LSeq::new(1);
+    for c in "abc".chars() {
+        site2.apply(site1.append(c));
+    }
+
+    let rebalance_ops = site1.rebalance();
+    assert_eq!(site1.iter().collect::<String>(), "abc");
+
+    // site2 hasn't seen the rebalance and inserts against the old identifiers
+    let insert_op = site2.insert_index(1, 'x');
+
+    for op in rebalance_ops {
+        site2.apply(op);
+    }
+    site1.apply(insert_op);
+
+    assert_eq!(site1.len(), 4);
+    assert!(site1.iter().any(|c| c == &'x'));
+    assert_eq!(
+        site1.iter().collect::<String>(),
+        site2.iter().collect::<String>()
+    );
+}
+
+#[test]
+fn test_delete_concurrent_with_rebalance_stays_deleted() {
+    let mut site1 = LSeq::new(0);
+    let mut site2 = LSeq::new(1);
+    for c in "abcd".chars() {
+        site2.apply(site1.append(c));
+    }
+
+    let delete = site1.delete_index(1).unwrap();
+    let rebalance_ops = site2.rebalance();
+
+    site2.apply(delete);
+    for op in rebalance_ops {
+        site1.apply(op);
+    }
+
+    assert_eq!(site1.iter().collect::<String>(), "acd");
+    assert_eq!(site2.iter().collect::<String>(), "acd");
+}
+
+#[test]
+fn test_delete_concurrent_with_reverse_stays_deleted() {
+    let mut site1 = LSeq::new(0);
+    let mut site2 = LSeq::new(1);
+    for c in "abcd".chars() {
+        site2.apply(site1.append(c));
+    }
+
+    let delete = site1.delete_index(1).unwrap();
+    let reverse_ops = site2.reverse();
+
+    site2.apply(delete);
+    for op in reverse_ops {
+        site1.apply(op);
+    }
+
+    assert_eq!(site1.iter().collect::<String>(), "dca");
+    assert_eq!(site2.iter().collect::<String>(), "dca");
+}
+
 #[test]
 fn test_delete_of_index() {
     let mut site1 = LSeq::new(0);
-    site1.insert_index(0, 'a');
-    site1.insert_index(1, 'b');
-    assert_eq!(site1.iter().collect::<String>(), "ab");
+    site1.insert_index(0, 'a');
+    site1.insert_index(1, 'b');
+    assert_eq!(site1.iter().collect::<String>(), "ab");
+
+    site1.delete_index(0);
+    assert_eq!(site1.iter().collect::<String>(), "b");
+}
+
+#[test]
+fn test_get() {
+    let mut site1 = LSeq::new(0);
+    site1.append('a');
+    site1.append('b');
+
+    assert_eq!(site1.get(0), Some(&'a'));
+    assert_eq!(site1.get(1), Some(&'b'));
+}
+
+#[test]
+fn test_max_identifier_depth_grows_with_boundary_inserts() {
+    let mut site = LSeq::new(0);
+    assert_eq!(site.max_identifier_depth(), 0);
+
+    site.insert_index(0, 'a');
+    let initial_depth = site.max_identifier_depth();
+    assert_eq!(initial_depth, 1);
+
+    // the root only has room for a handful of identifiers, so repeatedly
+    // inserting at the front must push identifiers deeper into the tree
+    for _ in 0..20 {
+        site.insert_index(0, 'a');
+    }
+    assert!(site.max_identifier_depth() > initial_depth);
+}
+
+#[test]
+fn test_alternating_strategy_keeps_prepends_shallow() {
+    let mut site = LSeq::with_strategy(0, AllocStrategy::Alternating);
+    for i in 0..10_000 {
+        site.insert_index(0, i);
+    }
+
+    assert_eq!(site.len(), 10_000);
+    assert_eq!(site.get(0), Some(&9_999));
+    // each boundary- level holds exponentially more prepends than the one above it,
+    // so the depth grows with the log of the number of elements
+    assert!(site.max_identifier_depth() <= 20);
+}
+
+#[test]
+fn test_alternating_strategy_keeps_appends_shallow() {
+    let mut site = LSeq::with_strategy(0, AllocStrategy::Alternating);
+    for i in 0..10_000 {
+        site.append(i);
+    }
+
+    assert_eq!(site.len(), 10_000);
+    assert_eq!(site.last(), Some(&9_999));
+    assert!(site.max_identifier_depth() <= 20);
+}
+
+#[test]
+fn test_forget_is_shared_with_mvreg() {
+    fn gc<C: Causal<SiteId>>(crdt: &mut C, bound: &VClock<SiteId>) {
+        crdt.forget(bound);
+    }
+
+    let mut site: LSeq<char, SiteId> = LSeq::new(0);
+    site.append('a');
+    site.append('b');
+    site.delete_index(0);
+
+    let mut reg = MVReg::new();
+    reg.apply(reg.write('a', reg.read().derive_add_ctx(0)));
+
+    let bound = VClock::from(Dot::new(0, 3));
+    let expected = site.clone();
+    gc(&mut site, &bound);
+    gc(&mut reg, &bound);
+
+    // the sequence has no tombstones to forget while the register drops its covered value
+    assert_eq!(site, expected);
+    assert!(reg.read().val.is_empty());
+}
+
+#[test]
+fn test_delete_leaves_reinserted_identifier_alone() {
+    let mut site0 = LSeq::new(0);
+    let mut site1 = LSeq::new(1);
+
+    let insert = site0.append('a');
+    let delete = site0.delete_index(0).unwrap();
+
+    // a later insert that happens to reuse the deleted identifier
+    let reinsert = Op::Insert {
+        id: insert.id().unwrap().clone(),
+        dot: Dot::new(0, 3),
+        val: 'b',
+        meta: (),
+    };
+
+    site1.apply(insert);
+    site1.apply(delete.clone());
+    site1.apply(reinsert);
+    assert_eq!(site1.validate_op(&delete), Err(OpError::UnknownId));
+    site1.apply(delete);
+    assert_eq!(site1.iter().collect::<String>(), "b");
+}
+
+#[test]
+fn test_move_item() {
+    let mut site0 = LSeq::new(0);
+    let mut site1 = LSeq::new(1);
+    for c in "abcd".chars() {
+        site1.apply(site0.append(c));
+    }
+
+    let op = site0.move_item(0, 2).unwrap();
+    assert_eq!(site0.iter().collect::<String>(), "bcad");
+    assert_eq!(site1.validate_op(&op), Ok(()));
+    site1.apply(op.clone());
+    site1.apply(op);
+    assert_eq!(site1.iter().collect::<String>(), "bcad");
+
+    assert_eq!(site0.move_item(3, 10), None);
+    assert_eq!(site0.move_item(4, 0), None);
+    site1.apply(site0.move_item(3, 0).unwrap());
+    assert_eq!(site1.iter().collect::<String>(), "dbca");
+    assert_eq!(site0.iter().collect::<String>(), "dbca");
+}
+
+#[test]
+fn test_decode_4x_json() {
+    // written by crdts 4.x: site 1 inserted 'a' then 'b' and deleted 'a'
+    let state = r#"{"seq":[{"id":{"path":[[5,1]]},"dot":{"actor":1,"counter":2},"val":"b"}],"gen":{"initial_base_bits":3,"boundary":10,"site_id":1},"dot":{"actor":1,"counter":3}}"#;
+    let insert = r#"{"Insert":{"id":{"path":[[3,1]]},"dot":{"actor":1,"counter":1},"val":"a"}}"#;
+    let delete = r#"{"Delete":{"remote":{"actor":1,"counter":1},"id":{"path":[[3,1]]},"dot":{"actor":1,"counter":3}}}"#;
+
+    let mut site: LSeq<char, SiteId> = serde_json::from_str(state).unwrap();
+    assert_eq!(site.iter().collect::<String>(), "b");
+
+    // a 4.x peer's ops apply as well
+    let mut replica = LSeq::new(2);
+    let insert: Op<char, SiteId> = serde_json::from_str(insert).unwrap();
+    assert_eq!(replica.validate_op(&insert), Ok(()));
+    replica.apply(insert);
+    assert_eq!(replica.iter().collect::<String>(), "a");
+    let delete: Op<char, SiteId> = serde_json::from_str(delete).unwrap();
+    assert_eq!(replica.validate_op(&delete), Ok(()));
+    replica.apply(delete);
+    assert!(replica.is_empty());
+
+    site.append('c');
+    assert_eq!(site.iter().collect::<String>(), "bc");
+}
+
+#[test]
+fn test_json_round_trip_after_move() {
+    let mut site0 = LSeq::new(0);
+    site0.extend("abcd".chars());
+    site0.move_item(0, 2).unwrap();
+
+    let json = serde_json::to_string(&site0).unwrap();
+    let restored: LSeq<char, SiteId> = serde_json::from_str(&json).unwrap();
+    assert_eq!(restored, site0);
+
+    let json = serde_json::to_string(&site0.snapshot()).unwrap();
+    let restored = LSeq::restore(serde_json::from_str(&json).unwrap());
+    assert_eq!(restored, site0);
+}
+
+#[test]
+fn test_concurrent_moves_of_same_item_converge() {
+    let mut site0 = LSeq::new(0);
+    let mut site1 = LSeq::new(1);
+    let mut site2 = LSeq::new(2);
+    for c in "abcde".chars() {
+        let op = site0.append(c);
+        site1.apply(op.clone());
+        site2.apply(op);
+    }
+
+    let move1 = site1.move_item(0, 4).unwrap();
+    let move2 = site2.move_item(0, 2).unwrap();
+
+    site1.apply(move2.clone());
+    site2.apply(move1.clone());
+    site0.apply(move2);
+    site0.apply(move1);
+
+    let text = site0.iter().collect::<String>();
+    assert_eq!(text, "bcade");
+    assert_eq!(site1.iter().collect::<String>(), text);
+    assert_eq!(site2.iter().collect::<String>(), text);
+
+    // a later move wins over the moves it has seen regardless of actor
+    let move0 = site0.move_item(2, 0).unwrap();
+    site1.apply(move0.clone());
+    site2.apply(move0);
+    assert_eq!(site1.iter().collect::<String>(), "abcde");
+    assert_eq!(site2.iter().collect::<String>(), "abcde");
+}
+
+#[test]
+fn test_move_concurrent_with_delete() {
+    let mut site0 = LSeq::new(0);
+    let mut site1 = LSeq::new(1);
+    for c in "abc".chars() {
+        site1.apply(site0.append(c));
+    }
+
+    let mv = site0.move_item(0, 2).unwrap();
+    let delete = site1.delete_index(0).unwrap();
+
+    site0.apply(delete);
+    assert_eq!(site1.validate_op(&mv), Err(OpError::UnknownId));
+    site1.apply(mv);
+
+    assert_eq!(site0.iter().collect::<String>(), "bc");
+    assert_eq!(site1.iter().collect::<String>(), "bc");
+}
+
+#[test]
+fn test_redelivered_insert_of_moved_item_is_ignored() {
+    let mut site0 = LSeq::new(0);
+    let mut site1 = LSeq::new(1);
+    let insert = site0.append('a');
+    site1.apply(insert.clone());
+    site1.apply(site0.append('b'));
+
+    site1.apply(site0.move_item(0, 1).unwrap());
+    site1.apply(insert);
+    assert_eq!(site1.iter().collect::<String>(), "ba");
+}
+
+#[test]
+fn test_diff_brings_remote_up_to_date() {
+    let mut replica_a = LSeq::new(0);
+    let mut replica_b = LSeq::new(1);
+    let mut clock_b = VClock::new();
+    for c in "ace".chars() {
+        let op = replica_a.append(c);
+        clock_b.apply(*op.dot());
+        replica_b.apply(op);
+    }
+
+    // edits B hasn't seen, including an element that is inserted then deleted
+    replica_a.insert_index(1, 'b');
+    replica_a.insert_index(3, 'd');
+    replica_a.append('x');
+    replica_a.delete_index(5);
+    replica_a.insert_many(5, vec!['f', 'g']);
+
+    let diff = replica_a.diff(&clock_b);
+    assert_eq!(diff.len(), 4);
+    assert!(diff.iter().all(|op| matches!(op, Op::Insert { .. })));
+    for op in diff {
+        assert_eq!(replica_b.validate_op(&op), Ok(()));
+        replica_b.apply(op);
+    }
+
+    assert!(replica_a.iter().eq(replica_b.iter()));
+    assert_eq!(replica_b.iter().collect::<String>(), "abcdefg");
+    assert!(replica_a.diff(&VClock::from(Dot::new(0, 9))).is_empty());
+}
+
+#[test]
+fn test_apply_all_is_all_or_nothing() {
+    let mut site0 = LSeq::new(0);
+    let mut site1: LSeq<char, SiteId> = LSeq::new(1);
+
+    // the delete is only valid once the insert before it in the batch is applied
+    let mut batch = vec![site0.append('a'), site0.append('b')];
+    batch.push(site0.delete_index(0).unwrap());
+    batch.push(site0.append('c'));
+
+    let before = bincode::serialize(&site1).unwrap();
+    let unknown = Op::Delete {
+        remote: Dot::new(0, 9),
+        id: batch[0].id().unwrap().clone(),
+        dot: Dot::new(0, 10),
+    };
+    let mut bad_batch = batch.clone();
+    bad_batch.insert(3, unknown);
+    assert_eq!(site1.apply_all(&bad_batch), Err((3, OpError::UnknownId)));
+    assert_eq!(bincode::serialize(&site1).unwrap(), before);
+
+    assert_eq!(site1.apply_all(&batch), Ok(()));
+    assert_eq!(site1.iter().collect::<String>(), "bc");
+    assert!(site1.iter().eq(site0.iter()));
+}
+
+#[test]
+fn test_apply_all_tracks_moves_and_deletes_earlier_in_the_batch() {
+    let mut site0 = LSeq::new(0);
+    let mut site1: LSeq<char, SiteId> = LSeq::new(1);
+    for c in "ab".chars() {
+        site1.apply(site0.append(c));
+    }
+
+    let batch = vec![
+        site0.append('c'),
+        site0.move_item(2, 0).unwrap(),
+        site0.move_item(1, 2).unwrap(),
+        site0.delete_index(0).unwrap(),
+    ];
+    assert_eq!(site1.apply_all(&batch), Ok(()));
+    assert!(site1.iter().eq(site0.iter()));
+
+    // an element deleted earlier in the batch can't be deleted or moved again
+    let stale_move = site0.clone().move_item(0, 1).unwrap();
+    let delete = site0.delete_index(0).unwrap();
+    let before = site1.clone();
+    assert_eq!(
+        site1.apply_all(&[delete.clone(), delete.clone()]),
+        Err((1, OpError::UnknownId))
+    );
+    assert_eq!(
+        site1.apply_all(&[delete.clone(), stale_move.clone()]),
+        Err((1, OpError::UnknownId))
+    );
+    assert_eq!(site1, before);
+    assert_eq!(site1.apply_all(&[stale_move, delete]), Ok(()));
+    assert!(site1.iter().eq(site0.iter()));
+}
+
+#[test]
+fn test_deletes_leave_no_tombstones() {
+    let mut site: LSeq<char, SiteId> = LSeq::new(0);
+    for c in "abcdefgh".chars() {
+        site.append(c);
+    }
+    let full = bincode::serialized_size(&site).unwrap();
+
+    site.delete_range(1, site.len());
+
+    // deleted elements are dropped right away rather than kept as tombstones
+    assert_eq!(site.iter().collect::<String>(), "a");
+    assert!(bincode::serialized_size(&site).unwrap() < full / 2);
+}
+
+#[test]
+fn test_from_iter() {
+    let empty: LSeq<char, SiteId> = std::iter::empty().collect();
+    assert!(empty.is_empty());
+    assert_eq!(empty.actor(), SiteId::default());
+
+    let seeded: LSeq<char, SiteId> = "hello".chars().collect();
+    let mut inserted: LSeq<char, SiteId> = LSeq::new(0);
+    let mut replica: LSeq<char, SiteId> = LSeq::new(1);
+    for (i, c) in "hello".chars().enumerate() {
+        replica.apply(inserted.insert_index(i, c));
+    }
+
+    assert_eq!(seeded.iter().collect::<String>(), "hello");
+    assert!(seeded.iter().eq(inserted.iter()));
+    assert!(seeded.iter().eq(replica.iter()));
+
+    // the seeded sequence keeps issuing ops from where it left off
+    let mut seeded = seeded;
+    assert_eq!(seeded.append('!').dot(), &Dot::new(0, 6));
+}
+
+#[test]
+fn test_contains() {
+    let mut site1 = LSeq::new(0);
+    site1.append('a');
+    site1.append('b');
+
+    assert!(site1.contains(&'a'));
+    assert!(!site1.contains(&'c'));
+
+    site1.delete_index(0);
+    assert!(!site1.contains(&'a'));
+    assert!(site1.contains(&'b'));
+}
+
+#[test]
+fn test_delete_with_zero_counter_dot_is_ignored() {
+    let mut site1 = LSeq::new(0);
+    site1.append('a');
+    let mut site2 = site1.clone();
+
+    let op = site2.delete_index(0).unwrap();
+    let malformed = match op {
+        Op::Delete {
+            remote,
+            id,
+            mut dot,
+        } => {
+            dot.counter = 0;
+            Op::Delete { remote, id, dot }
+        }
+        _ => panic!("expected a delete op"),
+    };
+
+    site1.apply(malformed);
+    assert_eq!(site1.iter().collect::<Vec<_>>(), vec![&'a']);
+}
+
+#[test]
+fn test_relative_position() {
+    let mut site1 = LSeq::new(0);
+    assert_eq!(site1.relative_position(0), 0.0);
+
+    site1.extend(vec!['a', 'b', 'c', 'd']);
+    assert_eq!(site1.relative_position(0), 0.0);
+    assert!((site1.relative_position(2) - 0.5).abs() < f64::EPSILON);
+    assert!((site1.relative_position(3) - 0.75).abs() < f64::EPSILON);
+    assert!((site1.relative_position(10) - 0.75).abs() < f64::EPSILON);
+}
+
+#[test]
+fn test_swap() {
+    let mut site1 = LSeq::new(0);
+    site1.extend(vec!['a', 'b', 'c', 'd']);
+
+    let ops = site1.swap(0, 2);
+    assert_eq!(ops.len(), 2);
+    assert_eq!(site1.iter().collect::<String>(), "cbad");
+
+    // swapping neighbors takes a single move
+    assert_eq!(site1.swap(2, 3).len(), 1);
+    assert_eq!(site1.iter().collect::<String>(), "cbda");
+    site1.swap(2, 3);
+
+    assert!(site1.swap(3, 3).is_empty());
+    assert!(site1.swap(1, 4).is_empty());
+    assert_eq!(site1.iter().collect::<String>(), "cbad");
+}
+
+#[test]
+fn test_concurrent_swaps_of_different_elements_converge() {
+    let mut site1 = LSeq::new(0);
+    let mut site2 = LSeq::new(1);
+    for val in "abcd".chars() {
+        site2.apply(site1.append(val));
+    }
+
+    let ops1 = site1.swap(0, 1);
+    let ops2 = site2.swap(2, 3);
+    ops2.into_iter().for_each(|op| site1.apply(op));
+    ops1.into_iter().for_each(|op| site2.apply(op));
+
+    assert_eq!(site1.iter().collect::<String>(), "badc");
+    assert_eq!(site2.iter().collect::<String>(), "badc");
+}
+
+#[test]
+fn test_concurrent_swaps_of_same_element_do_not_duplicate() {
+    let mut site1 = LSeq::new(0);
+    let mut site2 = LSeq::new(1);
+    for val in "abcd".chars() {
+        site2.apply(site1.append(val));
+    }
+
+    let ops1 = site1.swap(0, 2);
+    let ops2 = site2.swap(0, 1);
+    ops2.into_iter().for_each(|op| site1.apply(op));
+    ops1.into_iter().for_each(|op| site2.apply(op));
+
+    let text = site1.iter().collect::<String>();
+    assert_eq!(site2.iter().collect::<String>(), text);
+    let mut sorted: Vec<char> = text.chars().collect();
+    sorted.sort_unstable();
+    assert_eq!(sorted, vec!['a', 'b', 'c', 'd']);
+}
+
+#[test]
+fn test_op_id() {
+    let mut site1 = LSeq::new(0);
+    let insert = site1.append('a');
+    let delete = site1.delete_index(0).unwrap();
+
+    assert_eq!(LSeq::op_id(&insert), Dot::new(0, 1));
+    assert_eq!(LSeq::op_id(&delete), Dot::new(0, 2));
+}
+
+#[test]
+fn test_value_mut() {
+    let mut site1 = LSeq::new(0);
+    site1.extend(vec!['a', 'b']);
+
+    *site1.value_mut(1).unwrap() = 'c';
+    assert_eq!(site1.get(1), Some(&'c'));
+    assert_eq!(site1.value_mut(2), None);
+}
+
+#[test]
+fn test_try_apply() {
+    let mut site1 = LSeq::new(0);
+    let mut site2 = LSeq::new(1);
+
+    let insert = site1.append('a');
+    assert_eq!(site2.try_apply(insert.clone()), Ok(()));
+    assert_eq!(site2.iter().collect::<Vec<_>>(), vec![&'a']);
+
+    let delete = site1.delete_index(0).unwrap();
+    assert_eq!(site2.try_apply(delete.clone()), Ok(()));
+    assert!(site2.is_empty());
+}
+
+#[test]
+fn test_try_apply_rejects_zero_counter_dot() {
+    let mut site1 = LSeq::new(0);
+    let mut site2 = LSeq::new(1);
+
+    let malformed = match site1.append('a') {
+        Op::Insert {
+            id,
+            mut dot,
+            val,
+            meta,
+        } => {
+            dot.counter = 0;
+            Op::Insert { id, dot, val, meta }
+        }
+        _ => panic!("expected an insert op"),
+    };
+
+    assert_eq!(site2.try_apply(malformed), Err(OpError::ZeroCounterDot));
+    assert!(site2.is_empty());
+}
+
+#[test]
+fn test_try_apply_rejects_delete_of_unknown_id() {
+    let mut site1 = LSeq::new(0);
+    let mut site2 = LSeq::new(1);
+
+    let insert = site1.append('a');
+    let delete = site1.delete_index(0).unwrap();
+
+    // the delete arrives before the insert it removes
+    assert_eq!(site2.try_apply(delete.clone()), Err(OpError::UnknownId));
+
+    site2.apply(insert);
+    assert_eq!(site2.try_apply(delete.clone()), Ok(()));
+
+    // and it is redelivered
+    assert_eq!(site2.try_apply(delete), Err(OpError::UnknownId));
+}
+
+#[test]
+fn test_validate_op_rejects_insert_at_taken_id() {
+    let mut site1 = LSeq::new(0);
+    let insert = site1.append('a');
+
+    // redelivering the insert is fine
+    assert_eq!(site1.validate_op(&insert), Ok(()));
+
+    let forged = match insert {
+        Op::Insert { id, meta, .. } => Op::Insert {
+            id,
+            dot: Dot::new(1, 1),
+            val: 'b',
+            meta,
+        },
+        _ => panic!("expected an insert op"),
+    };
+    assert_eq!(site1.validate_op(&forged), Err(OpError::DuplicateId));
+    assert_eq!(site1.try_apply(forged), Err(OpError::DuplicateId));
+    assert_eq!(site1.iter().collect::<String>(), "a");
+}
+
+#[test]
+fn test_validate_op_rejects_empty_insert_run() {
+    let mut site1: LSeq<char, u8> = LSeq::new(0);
+    let empty = Op::InsertMany {
+        ids: vec![],
+        dot: Dot::new(1, 1),
+        vals: vec![],
+        meta: (),
+    };
+
+    assert_eq!(empty.id(), None);
+    assert_eq!(site1.validate_op(&empty), Err(OpError::MalformedRun));
+    assert_eq!(site1.try_apply(empty), Err(OpError::MalformedRun));
+    assert!(site1.is_empty());
+}
+
+#[test]
+fn test_validate_op_rejects_insert_run_with_mismatched_lengths() {
+    let mut site1 = LSeq::new(0);
+    let mut site2 = LSeq::new(1);
+
+    let mismatched = match site1.insert_many(0, vec!['a', 'b']).unwrap() {
+        Op::InsertMany { ids, dot, meta, .. } => Op::InsertMany {
+            ids,
+            dot,
+            vals: vec!['a', 'b', 'c'],
+            meta,
+        },
+        _ => panic!("expected an insert many op"),
+    };
+
+    assert_eq!(site2.validate_op(&mismatched), Err(OpError::MalformedRun));
+    assert_eq!(
+        site2.try_apply(mismatched.clone()),
+        Err(OpError::MalformedRun)
+    );
+
+    // apply drops the run rather than pairing up what it can
+    site2.apply(mismatched);
+    assert!(site2.is_empty());
+}
+
+#[test]
+fn test_find_all() {
+    let mut site1 = LSeq::new(0);
+    site1.extend(vec![1, 2, 3, 4, 5, 6]);
+    site1.delete_index(1);
+
+    assert_eq!(site1.find_all(|val| val % 2 == 0), vec![2, 4]);
+    assert!(site1.find_all(|val| *val > 6).is_empty());
+}
+
+#[test]
+fn test_extend_from_ships_individual_inserts() {
+    let mut site1: LSeq<char, SiteId> = LSeq::new(1);
+    let mut site2: LSeq<char, SiteId> = LSeq::new(2);
+    for c in "ab".chars() {
+        site2.apply(site1.append(c));
+    }
+
+    assert_eq!(site1.extend_from(1, "".chars()), vec![]);
+
+    let ops = site1.extend_from(1, "xyz".chars());
+    assert_eq!(site1.iter().collect::<String>(), "axyzb");
+    assert_eq!(ops.len(), 3);
+    for op in ops {
+        assert!(matches!(op, Op::Insert { .. }));
+        site2.apply(op);
+    }
+    assert_eq!(site2.iter().collect::<String>(), "axyzb");
+}
+
+#[test]
+fn test_insert_many_string_lands_in_order() {
+    let mut site1 = LSeq::new(0);
+    let mut site2 = LSeq::new(1);
+
+    let op = site1.insert_many(0, "hello".chars().collect()).unwrap();
+    assert_eq!(site1.iter().collect::<String>(), "hello");
+
+    site2.apply(op);
+    assert_eq!(site2.iter().collect::<String>(), "hello");
+}
+
+#[test]
+fn test_insert_many_converges_with_concurrent_insert() {
+    let mut site1 = LSeq::new(0);
+    let mut site2 = LSeq::new(1);
+    let op = site1.append('!');
+    site2.apply(op);
 
-    site1.delete_index(0);
-    assert_eq!(site1.iter().collect::<String>(), "b");
+    // both sites insert before the '!' concurrently
+    let batch = site1.insert_many(0, "hello".chars().collect()).unwrap();
+    let single = site2.insert_index(0, 'x');
+
+    site1.apply(single);
+    site2.apply(batch);
+
+    let text: String = site1.iter().collect();
+    assert_eq!(text, site2.iter().collect::<String>());
+    // the concurrent insert may land inside the batch, but the batch keeps its order
+    assert_eq!(text.replace('x', ""), "hello!");
 }
 
 #[test]
-fn test_get() {
+fn test_insert_many_matches_individual_inserts() {
     let mut site1 = LSeq::new(0);
-    site1.append('a');
-    site1.append('b');
+    let mut batched = LSeq::new(1);
+    let mut individual = LSeq::new(2);
+    for val in "ad".chars() {
+        let op = site1.append(val);
+        batched.apply(op.clone());
+        individual.apply(op);
+    }
 
-    assert_eq!(site1.get(0), Some(&'a'));
-    assert_eq!(site1.get(1), Some(&'b'));
+    let op = site1.insert_many(1, vec!['b', 'c']).unwrap();
+    assert_eq!(site1.iter().collect::<String>(), "abcd");
+    assert_eq!(site1.insert_many(0, vec![]), None);
+
+    // expand the batch into the individual inserts it stands for
+    let inserts: Vec<_> = match op.clone() {
+        Op::InsertMany {
+            ids,
+            dot,
+            vals,
+            meta,
+        } => ids
+            .into_iter()
+            .zip(vals)
+            .enumerate()
+            .map(|(i, (id, val))| Op::Insert {
+                id,
+                dot: Dot::new(dot.actor, dot.counter + i as u64),
+                val,
+                meta,
+            })
+            .collect(),
+        _ => panic!("expected an insert many op"),
+    };
+    assert_eq!(inserts.len(), 2);
+
+    batched.apply(op);
+    inserts.into_iter().for_each(|op| individual.apply(op));
+    assert_eq!(batched.iter().collect::<String>(), "abcd");
+    assert_eq!(individual.iter().collect::<String>(), "abcd");
+
+    // the elements carry the same identifiers and dots on both replicas
+    for ix in 0..4 {
+        match (batched.delete_index(0), individual.delete_index(0)) {
+            (
+                Some(Op::Delete {
+                    remote: r1,
+                    id: id1,
+                    ..
+                }),
+                Some(Op::Delete {
+                    remote: r2,
+                    id: id2,
+                    ..
+                }),
+            ) => {
+                assert_eq!(r1, r2, "dots differ at {}", ix);
+                assert_eq!(id1, id2, "identifiers differ at {}", ix);
+            }
+            ops => panic!("expected two deletes, got {:?}", ops),
+        }
+    }
+}
+
+#[test]
+fn test_display() {
+    let mut text = LSeq::new(0);
+    text.extend("hello".chars());
+    text.delete_index(4);
+    text.append('!');
+    assert_eq!(text.to_string(), "hell!");
+
+    let mut numbers = LSeq::new(0);
+    numbers.extend(vec![1, 20, 3]);
+    assert_eq!(format!("{:#}", numbers), "1, 20, 3");
+    assert_eq!(LSeq::<u8, u8>::new(0).to_string(), "");
+}
+
+#[test]
+fn test_neighbors() {
+    let mut site1 = LSeq::new(0);
+    site1.extend(vec!['a', 'b', 'c']);
+
+    assert_eq!(site1.neighbors(1), (Some(&'a'), Some(&'b')));
+    assert_eq!(site1.neighbors(0), (None, Some(&'a')));
+    assert_eq!(site1.neighbors(3), (Some(&'c'), None));
+    assert_eq!(site1.neighbors(4), (None, None));
+}
+
+#[test]
+fn test_extend() {
+    let mut site1 = LSeq::new(0);
+    site1.extend(vec![1, 2, 3]);
+    assert_eq!(site1.iter().collect::<Vec<_>>(), vec![&1, &2, &3]);
+
+    site1.extend(vec![4]);
+    assert_eq!(site1.iter().collect::<Vec<_>>(), vec![&1, &2, &3, &4]);
+}
+
+#[test]
+fn test_three_way_concurrent_insert_at_same_position() {
+    // identifiers are allocated randomly, so cover a good number of allocations
+    for _ in 0..100 {
+        let mut site0 = LSeq::new(0);
+        let base = [site0.append('p'), site0.append('q')];
+
+        let mut site1 = LSeq::new(1);
+        let mut site2 = LSeq::new(2);
+        for op in base.iter().cloned() {
+            site1.apply(op.clone());
+            site2.apply(op);
+        }
+
+        // all three sites insert between 'p' and 'q' without seeing each other's edits
+        let op0 = site0.insert_index(1, 'a');
+        let op1 = site1.insert_index(1, 'b');
+        let op2 = site2.insert_index(1, 'c');
+
+        site0.apply(op1.clone());
+        site0.apply(op2.clone());
+        site1.apply(op2);
+        site1.apply(op0.clone());
+        site2.apply(op0);
+        site2.apply(op1);
+
+        let content: Vec<char> = site0.iter().cloned().collect();
+        assert_eq!(site1.iter().cloned().collect::<Vec<_>>(), content);
+        assert_eq!(site2.iter().cloned().collect::<Vec<_>>(), content);
+
+        // equal positions are tie-broken by the inserting site, so whatever
+        // the order, all three values land between 'p' and 'q'
+        assert_eq!(content.len(), 5);
+        assert_eq!(content[0], 'p');
+        assert_eq!(content[4], 'q');
+        let mut middle = content[1..4].to_vec();
+        middle.sort();
+        assert_eq!(middle, vec!['a', 'b', 'c']);
+    }
+}
+
+#[test]
+fn test_repeated_inserts_between_adjacent_atoms() {
+    let mut site1 = LSeq::new(0);
+    let mut site2 = LSeq::new(1);
+    site2.apply(site1.append(0));
+    site2.apply(site1.append(u8::MAX));
+
+    // always insert right after the first atom, so we keep allocating
+    // into an ever shrinking gap between two neighbouring atoms
+    for i in 1..=30 {
+        site2.apply(site1.insert_index(1, i));
+    }
+
+    let mut expected: Vec<u8> = vec![0];
+    expected.extend((1..=30).rev());
+    expected.push(u8::MAX);
+
+    assert_eq!(site1.iter().cloned().collect::<Vec<_>>(), expected);
+    assert_eq!(site2.iter().cloned().collect::<Vec<_>>(), expected);
 }
 
 #[test]
@@ -137,10 +1046,9 @@ fn test_insert_followed_by_deletes() {
         let insert_op = site1.insert_index(ix, c);
         site2.apply(insert_op);
 
-        let delete_op =
-            site2
-                .delete_index(ix)
-                .expect(&format!("ix@{} was out of bounds@{}", ix, site2.len()));
+        let delete_op = site2
+            .delete_index(ix)
+            .unwrap_or_else(|| panic!("ix@{} was out of bounds@{}", ix, site2.len()));
         site1.apply(delete_op);
     }
 
@@ -187,6 +1095,35 @@ fn test_mutual_insert_qc1() {
 }
 
 quickcheck! {
+    fn prop_apply_all_matches_staging_on_a_clone(plan: Vec<(u8, usize, usize)>, rotate: usize) -> bool {
+        let mut site0 = LSeq::new(0);
+        let mut ops = Vec::new();
+        for (kind, a, b) in plan {
+            let len = site0.len();
+            match kind % 3 {
+                0 => ops.push(site0.insert_index(a % (len + 1), kind)),
+                1 if len > 0 => ops.extend(site0.delete_index(a % len)),
+                _ if len > 0 => ops.extend(site0.move_item(a % len, b % len)),
+                _ => (),
+            }
+        }
+        // out of order, most batches fail part way through
+        let len = ops.len();
+        ops.rotate_left(rotate % cmp::max(len, 1));
+
+        let mut staged = LSeq::new(1);
+        let expected = ops.iter().enumerate().try_for_each(|(i, op)| {
+            staged.validate_op(op).map_err(|err| (i, err))?;
+            staged.apply(op.clone());
+            Ok(())
+        });
+        let mut site1 = LSeq::new(1);
+        let result = site1.apply_all(&ops);
+
+        let expected_state = if expected.is_ok() { staged } else { LSeq::new(1) };
+        result == expected && site1 == expected_state
+    }
+
     fn prop_mutual_inserting(plan: Vec<(u8, usize, bool)>) -> bool {
         let mut site0 = LSeq::new(0);
         let mut site1 = LSeq::new(1);
@@ -246,6 +1183,67 @@ quickcheck! {
         TestResult::from_bool(site1_text == site2_text)
     }
 
+    fn prop_ops_commute(op1: OperationList, op2: OperationList) -> bool {
+        let mut site1 = LSeq::new(0);
+        let mut site2 = LSeq::new(1);
+        op1.0.iter().chain(op2.0.iter()).for_each(|op| site1.apply(op.clone()));
+        op2.0.iter().chain(op1.0.iter()).for_each(|op| site2.apply(op.clone()));
+
+        site1.iter().eq(site2.iter())
+    }
+
+    fn prop_ops_associative(op1: OperationList, op2: OperationList, op3: OperationList) -> bool {
+        // (op1 op2) op3: a site that has seen op1 and op2 then receives op3
+        let mut site1 = LSeq::new(0);
+        op1.0.iter().chain(&op2.0).chain(&op3.0).for_each(|op| site1.apply(op.clone()));
+
+        // op1 (op2 op3): a site that has seen op2 and op3 then receives op1
+        let mut site2 = LSeq::new(1);
+        op2.0.iter().chain(&op3.0).chain(&op1.0).for_each(|op| site2.apply(op.clone()));
+
+        // and one that received them grouped the other way around
+        let mut site3 = LSeq::new(2);
+        op3.0.iter().chain(&op1.0).chain(&op2.0).for_each(|op| site3.apply(op.clone()));
+
+        site1.iter().eq(site2.iter()) && site2.iter().eq(site3.iter())
+    }
+
+    fn prop_concurrent_inserts_between_same_neighbors_converge(
+        base: Vec<u8>,
+        ix: usize,
+        edits0: Vec<(u8, bool)>,
+        edits1: Vec<(u8, bool)>
+    ) -> bool {
+        let mut site0: LSeq<u8, SiteId> = LSeq::new(0);
+        let mut site1: LSeq<u8, SiteId> = LSeq::new(1);
+        for val in base {
+            site1.apply(site0.append(val));
+        }
+
+        // both sites edit at the same position, so every insert lands between the same
+        // pair of neighbors, and a delete may remove a neighbor the other site is using
+        let ix = ix % (site0.len() + 1);
+        let concurrent_ops = |site: &mut LSeq<u8, SiteId>, edits: Vec<(u8, bool)>| {
+            edits
+                .into_iter()
+                .filter_map(|(val, delete)| {
+                    if delete {
+                        site.delete_index(ix)
+                    } else {
+                        Some(site.insert_index(cmp::min(ix, site.len()), val))
+                    }
+                })
+                .collect::<Vec<_>>()
+        };
+        let ops0 = concurrent_ops(&mut site0.clone(), edits0);
+        let ops1 = concurrent_ops(&mut site1.clone(), edits1);
+
+        ops0.iter().chain(&ops1).for_each(|op| site0.apply(op.clone()));
+        ops1.iter().chain(&ops0).for_each(|op| site1.apply(op.clone()));
+
+        site0.iter().eq(site1.iter())
+    }
+
     fn prop_ops_are_idempotent(ops: OperationList) -> TestResult {
         let mut site1 = LSeq::new(0);
         let mut site2 = LSeq::new(1);
@@ -265,6 +1263,58 @@ quickcheck! {
         TestResult::from_bool(site1_text == site2_text)
     }
 
+    fn prop_serde_round_trip(site: LSeq<u8, SiteId>, edits: Vec<(u8, usize)>) -> bool {
+        let json = serde_json::to_string(&site).unwrap();
+        assert_eq!(serde_json::from_str::<LSeq<u8, SiteId>>(&json).unwrap(), site);
+
+        let bytes = bincode::serialize(&site).unwrap();
+        let mut restored: LSeq<u8, SiteId> = bincode::deserialize(&bytes).unwrap();
+        assert_eq!(restored, site);
+
+        // the restored replica should keep converging with the original
+        let mut site = site;
+        for (elem, idx) in edits {
+            let op = site.insert_index(idx % (site.len() + 1), elem);
+            restored.apply(op);
+        }
+
+        site.iter().eq(restored.iter())
+    }
+
+    fn prop_restore_snapshot(site: LSeq<u8, SiteId>, edits: Vec<(u8, usize)>) -> bool {
+        let mut restored = LSeq::restore(site.snapshot());
+        assert_eq!(restored, site);
+
+        // the restored site keeps issuing ops the original's replicas can apply
+        let mut replica = site;
+        for (elem, idx) in edits {
+            let op = restored.insert_index(idx % (restored.len() + 1), elem);
+            assert_eq!(replica.validate_op(&op), Ok(()));
+            replica.apply(op);
+        }
+        replica.iter().eq(restored.iter())
+    }
+
+    fn prop_op_serde_round_trip(edits: Vec<(u8, usize, bool)>) -> bool {
+        let mut site: LSeq<u8, SiteId> = LSeq::new(0);
+        let mut ops = Vec::new();
+        for (elem, idx, delete) in edits {
+            let ix = idx % (site.len() + 1);
+            ops.extend(if delete {
+                site.delete_index(ix)
+            } else {
+                site.insert_many(ix, vec![elem, elem])
+            });
+        }
+
+        ops.into_iter().all(|op| {
+            let bytes = bincode::serialize(&op).unwrap();
+            let json = serde_json::to_string(&op).unwrap();
+            bincode::deserialize::<Op<u8, SiteId>>(&bytes).unwrap() == op
+                && serde_json::from_str::<Op<u8, SiteId>>(&json).unwrap() == op
+        })
+    }
+
     fn prop_len_is_proportional_to_ops(oplist: OperationList) -> TestResult {
         let mut expected_len = 0;
         let mut site1 = LSeq::new(0);
@@ -273,10 +1323,471 @@ quickcheck! {
             match op {
                 Op::Insert { .. } => expected_len += 1,
                 Op::Delete { .. } => expected_len -= 1,
+                Op::InsertMany { ref vals, .. } => expected_len += vals.len(),
+                Op::Move { .. } => (),
             };
             site1.apply(op);
         }
 
         TestResult::from_bool(site1.len() == expected_len)
     }
+
+    fn prop_iter_converges_regardless_of_delivery_order(
+        edits0: Vec<(u8, usize, bool)>,
+        edits1: Vec<(u8, usize, bool)>
+    ) -> bool {
+        // each site edits concurrently, deletes only target elements it has seen
+        let concurrent_ops = |site: &mut LSeq<u8, SiteId>, edits: Vec<(u8, usize, bool)>| {
+            edits
+                .into_iter()
+                .filter_map(|(elem, idx, delete)| {
+                    if delete {
+                        site.delete_index(idx % (site.len() + 1))
+                    } else {
+                        Some(site.insert_index(idx % (site.len() + 1), elem))
+                    }
+                })
+                .collect::<Vec<_>>()
+        };
+        let ops0 = concurrent_ops(&mut LSeq::new(0), edits0);
+        let ops1 = concurrent_ops(&mut LSeq::new(1), edits1);
+
+        let mut replica_a: LSeq<u8, SiteId> = LSeq::new(2);
+        let mut replica_b: LSeq<u8, SiteId> = LSeq::new(3);
+        ops0.iter().chain(ops1.iter()).for_each(|op| replica_a.apply(op.clone()));
+        ops1.iter().chain(ops0.iter()).for_each(|op| replica_b.apply(op.clone()));
+
+        replica_a.len() == replica_b.len()
+            && replica_a.iter().collect::<Vec<_>>() == replica_b.iter().collect::<Vec<_>>()
+    }
+
+    fn prop_merge_delta_same_as_applying_ops(edits: Vec<(u8, usize, bool)>, acked: usize) -> bool {
+        let mut site: LSeq<u8, SiteId> = LSeq::new(0);
+        let ops: Vec<_> = edits
+            .into_iter()
+            .filter_map(|(elem, idx, delete)| {
+                if delete {
+                    site.delete_index(idx % (site.len() + 1))
+                } else {
+                    Some(site.insert_index(idx % (site.len() + 1), elem))
+                }
+            })
+            .collect();
+
+        // the peer has acked a prefix of the ops, tracking the clock of what it has seen
+        let (acked_ops, unacked_ops) = ops.split_at(acked % (ops.len() + 1));
+        let mut peer: LSeq<u8, SiteId> = LSeq::new(1);
+        let mut peer_clock = VClock::new();
+        for op in acked_ops {
+            peer.apply(op.clone());
+            peer_clock.apply(*op.dot());
+        }
+
+        let mut replayed = peer.clone();
+        unacked_ops.iter().for_each(|op| replayed.apply(op.clone()));
+
+        // deletes aren't carried by deltas and still travel as ops, ahead of the delta
+        // since a later insert may reuse a deleted identifier
+        unacked_ops
+            .iter()
+            .filter(|op| matches!(op, Op::Delete { .. }))
+            .for_each(|op| peer.apply(op.clone()));
+        peer.merge_delta(site.delta(&peer_clock));
+
+        peer.iter().eq(replayed.iter()) && peer.iter().eq(site.iter())
+    }
+
+    fn prop_diff_same_as_applying_ops(edits: Vec<(u8, usize, bool)>, acked: usize) -> bool {
+        let mut site: LSeq<u8, SiteId> = LSeq::new(0);
+        let ops: Vec<_> = edits
+            .into_iter()
+            .filter_map(|(elem, idx, delete)| {
+                if delete {
+                    site.delete_index(idx % (site.len() + 1))
+                } else {
+                    Some(site.insert_index(idx % (site.len() + 1), elem))
+                }
+            })
+            .collect();
+
+        let (acked_ops, unacked_ops) = ops.split_at(acked % (ops.len() + 1));
+        let mut peer: LSeq<u8, SiteId> = LSeq::new(1);
+        let mut peer_clock = VClock::new();
+        for op in acked_ops {
+            peer.apply(op.clone());
+            peer_clock.apply(*op.dot());
+        }
+
+        unacked_ops
+            .iter()
+            .filter(|op| matches!(op, Op::Delete { .. }))
+            .for_each(|op| peer.apply(op.clone()));
+        site.diff(&peer_clock).into_iter().for_each(|op| peer.apply(op));
+
+        peer.iter().eq(site.iter())
+    }
+
+    fn prop_order_keys_match_sequence_order(ops: OperationList) -> bool {
+        let mut site1 = LSeq::new(0);
+        for op in ops.0.into_iter() {
+            site1.apply(op);
+        }
+
+        let keys: Vec<Vec<u8>> = (0..site1.len()).map(|ix| site1.order_key(ix).unwrap()).collect();
+        keys.windows(2).all(|pair| pair[0] < pair[1])
+    }
+}
+
+#[test]
+fn test_order_keys_match_iter_order() {
+    let mut site1: LSeq<char, String> = LSeq::new("a".to_string());
+    let mut site2: LSeq<char, String> = LSeq::new("a\0b".to_string());
+
+    for (i, c) in "hello".chars().enumerate() {
+        site1.insert_index(i, c);
+    }
+    for op in (0..5)
+        .map(|i| site2.insert_index(i, 'x'))
+        .collect::<Vec<_>>()
+    {
+        site1.apply(op);
+    }
+    site1.insert_index(3, 'y');
+
+    let mut keyed: Vec<(Vec<u8>, char)> = (0..site1.len())
+        .map(|ix| (site1.order_key(ix).unwrap(), *site1.get(ix).unwrap()))
+        .collect();
+    keyed.reverse();
+    keyed.sort();
+
+    let sorted: Vec<char> = keyed.into_iter().map(|(_, c)| c).collect();
+    let expected: Vec<char> = site1.iter().cloned().collect();
+    assert_eq!(sorted, expected);
+    assert_eq!(site1.order_key(site1.len()), None);
+}
+
+#[test]
+fn test_metadata_survives_merge() {
+    let mut site1: LSeq<char, u8, &str> = LSeq::with_metadata(1);
+    let mut site2: LSeq<char, u8, &str> = LSeq::with_metadata(2);
+
+    let op1 = site1.insert_index_with_meta(0, 'a', "alice");
+    let op2 = site2.insert_index_with_meta(0, 'b', "bob");
+    site1.apply(op2);
+    site2.apply(op1);
+
+    let attributed = |site: &LSeq<char, u8, &'static str>| -> Vec<(char, &str)> {
+        (0..site.len())
+            .map(|ix| (*site.get(ix).unwrap(), *site.author_at(ix).unwrap()))
+            .collect()
+    };
+    let authors = attributed(&site1);
+    assert_eq!(authors, attributed(&site2));
+    assert!(authors.contains(&('a', "alice")));
+    assert!(authors.contains(&('b', "bob")));
+    assert_eq!(site1.author_at(2), None);
+
+    // moving an element keeps its metadata
+    for op in site1.swap(0, 1) {
+        site2.apply(op);
+    }
+    assert_eq!(attributed(&site2), vec![authors[1], authors[0]]);
+}
+
+#[test]
+fn test_delete_does_not_remove_concurrent_insert_of_same_value() {
+    let mut site1: LSeq<char, SiteId> = LSeq::new(1);
+    let mut site2: LSeq<char, SiteId> = LSeq::new(2);
+    let insert = site1.append('a');
+    site2.apply(insert);
+
+    // site1 deletes its 'a' while site2 concurrently inserts another 'a' in front of it
+    let delete = site1.delete_index(0).unwrap();
+    let reinsert = site2.insert_index(0, 'a');
+
+    site1.apply(reinsert.clone());
+    site2.apply(delete);
+
+    assert_eq!(site1.iter().collect::<String>(), "a");
+    assert_eq!(site2.iter().collect::<String>(), "a");
+    assert_eq!(site1.order_key(0), site2.order_key(0));
+    assert_eq!(site1.order_key(0), Some(reinsert.id().unwrap().order_key()));
+}
+
+#[test]
+fn test_cmp_positions_matches_iter_order() {
+    let mut site1 = LSeq::new(0);
+    let c = site1.append('c');
+    let a = site1.insert_index(0, 'a');
+    let b = site1.insert_index(1, 'b');
+    assert_eq!(site1.iter().collect::<String>(), "abc");
+
+    assert_eq!(
+        site1.cmp_positions(a.id().unwrap(), b.id().unwrap()),
+        Ordering::Less
+    );
+    assert_eq!(
+        site1.cmp_positions(c.id().unwrap(), b.id().unwrap()),
+        Ordering::Greater
+    );
+    assert_eq!(
+        site1.cmp_positions(a.id().unwrap(), a.id().unwrap()),
+        Ordering::Equal
+    );
+
+    // positions can still be compared after their elements are deleted
+    site1.delete_index(0);
+    assert_eq!(
+        site1.cmp_positions(a.id().unwrap(), c.id().unwrap()),
+        Ordering::Less
+    );
+}
+
+#[test]
+fn test_squash_cancels_insert_and_its_delete() {
+    let mut site1: LSeq<char, SiteId> = LSeq::new(1);
+    let insert = site1.append('a');
+    let delete = site1.delete_index(0).unwrap();
+
+    assert_eq!(Op::squash(&[insert, delete]), vec![]);
+}
+
+#[test]
+fn test_squash_keeps_net_effect() {
+    let mut site1: LSeq<char, SiteId> = LSeq::new(1);
+    let existing = site1.append('x');
+    let mut site2 = site1.clone();
+
+    let mut batch = vec![site1.insert_many(1, vec!['a', 'b', 'c']).unwrap()];
+    batch.push(site1.delete_index(2).unwrap());
+    batch.push(site1.delete_index(0).unwrap());
+
+    let squashed = Op::squash(&batch);
+    assert_eq!(squashed.len(), 3);
+    assert!(squashed
+        .iter()
+        .any(|op| matches!(op, Op::Delete { remote, .. } if remote == existing.dot())));
+    for op in squashed {
+        site2.apply(op);
+    }
+    assert_eq!(site2.iter().collect::<String>(), "ac");
+    assert_eq!(
+        site2.iter().collect::<String>(),
+        site1.iter().collect::<String>()
+    );
+}
+
+#[test]
+fn test_iter_range() {
+    let mut site1: LSeq<u8, SiteId> = LSeq::new(1);
+    site1.extend(0..10);
+
+    assert_eq!(site1.iter_range(2, 5).collect::<Vec<_>>(), vec![&2, &3, &4]);
+    assert_eq!(site1.iter_range(8, 20).collect::<Vec<_>>(), vec![&8, &9]);
+    assert_eq!(site1.iter_range(12, 20).count(), 0);
+    assert_eq!(site1.iter_range(5, 2).count(), 0);
+    assert_eq!(site1.iter_from(7).collect::<Vec<_>>(), vec![&7, &8, &9]);
+}
+
+#[test]
+fn test_dedup() {
+    let mut site1: LSeq<u8, SiteId> = LSeq::new(1);
+    site1.extend(vec![1, 1, 2, 2, 2, 3]);
+    let mut site2 = site1.clone();
+
+    let ops = site1.dedup();
+    assert_eq!(ops.len(), 3);
+    assert!(ops.iter().all(|op| matches!(op, Op::Delete { .. })));
+    assert_eq!(site1.iter().collect::<Vec<_>>(), vec![&1, &2, &3]);
+
+    for op in ops {
+        site2.apply(op);
+    }
+    assert_eq!(site2.iter().collect::<Vec<_>>(), vec![&1, &2, &3]);
+    assert!(site1.dedup().is_empty());
+}
+
+#[test]
+fn test_cursor_follows_remote_edits() {
+    let mut site1: LSeq<char, SiteId> = LSeq::new(1);
+    let mut site2: LSeq<char, SiteId> = LSeq::new(2);
+    for c in "abcd".chars() {
+        site2.apply(site1.append(c));
+    }
+
+    // the caret sits on 'c'
+    let cursor = site1.cursor(2).unwrap();
+    assert_eq!(site1.cursor(4), None);
+
+    // a remote insert before the cursor shifts it right
+    site1.apply(site2.insert_index(0, 'x'));
+    assert_eq!(site1.resolve_cursor(&cursor), Some(3));
+    assert_eq!(site1.get(3), Some(&'c'));
+
+    // a remote delete before the cursor shifts it left
+    site1.apply(site2.delete_index(1).unwrap());
+    site1.apply(site2.delete_index(1).unwrap());
+    assert_eq!(site1.iter().collect::<String>(), "xcd");
+    assert_eq!(site1.resolve_cursor(&cursor), Some(1));
+    assert_eq!(site1.get(1), Some(&'c'));
+
+    // the cursor resolves the same way at the other site after shipping it over
+    let shipped: Cursor<SiteId> =
+        bincode::deserialize(&bincode::serialize(&cursor).unwrap()).unwrap();
+    assert_eq!(site2.resolve_cursor(&shipped), Some(1));
+}
+
+#[test]
+fn test_cursor_on_deleted_element_resolves_to_neighbor() {
+    let mut site: LSeq<char, SiteId> = LSeq::new(1);
+    for c in "abc".chars() {
+        site.append(c);
+    }
+
+    // deleting the cursor's element moves it onto the following element
+    let middle = site.cursor(1).unwrap();
+    site.delete_index(1);
+    assert_eq!(site.resolve_cursor(&middle), Some(1));
+    assert_eq!(site.get(1), Some(&'c'));
+
+    // at the end of the sequence it falls back to the last element
+    let last = site.cursor(1).unwrap();
+    site.delete_index(1);
+    assert_eq!(site.resolve_cursor(&last), Some(0));
+
+    site.delete_index(0);
+    assert_eq!(site.resolve_cursor(&last), None);
+}
+
+#[test]
+fn test_cursor_follows_remote_moves() {
+    let mut site1: LSeq<char, SiteId> = LSeq::new(1);
+    let mut site2: LSeq<char, SiteId> = LSeq::new(2);
+    for c in "abcd".chars() {
+        site2.apply(site1.append(c));
+    }
+
+    // the caret sits on 'b'
+    let cursor = site1.cursor(1).unwrap();
+
+    // a remote swap moves 'b' to the end
+    for op in site2.swap(1, 3) {
+        site1.apply(op);
+    }
+    assert_eq!(site1.iter().collect::<String>(), "adcb");
+    assert_eq!(site1.resolve_cursor(&cursor), Some(3));
+
+    // a remote move brings it to the front
+    site1.apply(site2.move_item(3, 0).unwrap());
+    assert_eq!(site1.iter().collect::<String>(), "badc");
+    assert_eq!(site1.resolve_cursor(&cursor), Some(0));
+
+    // once the moved element is deleted, the cursor falls back to the position it was
+    // taken at, between 'a' and 'c', where 'd' may have been moved to on either side
+    site1.apply(site2.delete_index(0).unwrap());
+    assert_eq!(site1.iter().collect::<String>(), "adc");
+    assert!(matches!(site1.resolve_cursor(&cursor), Some(1) | Some(2)));
+}
+
+#[test]
+fn test_cursor_stability_across_rebalance() {
+    let mut site1: LSeq<char, SiteId> = LSeq::new(1);
+    site1.append('a');
+    let cursor = site1.append('b');
+    site1.append('c');
+
+    // the cursor follows its element through unrelated edits
+    site1.insert_index(0, 'x');
+    site1.delete_index(3);
+    assert_eq!(site1.iter().collect::<String>(), "xab");
+    assert_eq!(site1.index_of(cursor.id().unwrap(), cursor.dot()), Some(2));
+
+    // a rebalance moves every element, and the cursor follows its element
+    site1.rebalance();
+    assert_eq!(site1.iter().collect::<String>(), "xab");
+    assert_eq!(site1.index_of(cursor.id().unwrap(), cursor.dot()), Some(2));
+
+    site1.reverse();
+    assert_eq!(site1.index_of(cursor.id().unwrap(), cursor.dot()), Some(0));
+}
+
+#[test]
+fn test_position_of_tracks_live_index() {
+    let mut site1: LSeq<char, SiteId> = LSeq::new(1);
+    let a = site1.append('a');
+    let b = site1.append('b');
+    let c = site1.append('c');
+    assert_eq!(site1.position_of(c.id().unwrap()), Some(2));
+
+    site1.delete_index(1);
+    assert_eq!(site1.get(1), Some(&'c'));
+    assert_eq!(site1.position_of(c.id().unwrap()), Some(1));
+    assert_eq!(site1.position_of(b.id().unwrap()), None);
+
+    site1.insert_index(0, 'x');
+    assert_eq!(site1.position_of(a.id().unwrap()), Some(1));
+    assert_eq!(site1.position_of(c.id().unwrap()), Some(2));
+    assert_eq!(
+        site1.get(site1.position_of(c.id().unwrap()).unwrap()),
+        Some(&'c')
+    );
+}
+
+#[test]
+fn test_delete_range_clamps() {
+    let mut site1: LSeq<char, SiteId> = LSeq::new(1);
+    site1.extend("abcdef".chars());
+
+    assert_eq!(site1.delete_range(1, 3).len(), 2);
+    assert_eq!(site1.iter().collect::<String>(), "adef");
+    assert_eq!(site1.delete_range(2, 100).len(), 2);
+    assert_eq!(site1.iter().collect::<String>(), "ad");
+    assert!(site1.delete_range(5, 10).is_empty());
+    assert!(site1.delete_range(1, 0).is_empty());
+    assert_eq!(site1.iter().collect::<String>(), "ad");
+}
+
+#[test]
+fn test_concurrent_overlapping_range_deletes_converge() {
+    let mut site1: LSeq<char, SiteId> = LSeq::new(1);
+    let mut site2: LSeq<char, SiteId> = LSeq::new(2);
+    for op in "abcdefg"
+        .chars()
+        .map(|c| site1.append(c))
+        .collect::<Vec<_>>()
+    {
+        site2.apply(op);
+    }
+
+    let ops1 = site1.delete_range(1, 4);
+    let ops2 = site2.delete_range(3, 6);
+    ops2.into_iter().for_each(|op| site1.apply(op));
+    ops1.into_iter().for_each(|op| site2.apply(op));
+
+    assert_eq!(site1.iter().collect::<String>(), "ag");
+    assert_eq!(site2.iter().collect::<String>(), "ag");
+}
+
+#[test]
+fn test_delete_value() {
+    let mut site1: LSeq<char, SiteId> = LSeq::new(1);
+    let mut site2: LSeq<char, SiteId> = LSeq::new(2);
+    for op in "banana"
+        .chars()
+        .map(|c| site1.append(c))
+        .collect::<Vec<_>>()
+    {
+        site2.apply(op);
+    }
+
+    let ops1 = site1.delete_value(&'a');
+    assert_eq!(ops1.len(), 3);
+    assert_eq!(site1.iter().collect::<String>(), "bnn");
+
+    // a concurrent delete of some of the same elements
+    let ops2 = site2.delete_range(0, 2);
+    ops2.into_iter().for_each(|op| site1.apply(op));
+    ops1.into_iter().for_each(|op| site2.apply(op));
+    assert_eq!(site1.iter().collect::<String>(), "nn");
+    assert_eq!(site2.iter().collect::<String>(), "nn");
 }
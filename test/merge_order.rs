@@ -0,0 +1,215 @@
+use crdts::lseq::{self, LSeq};
+use crdts::{pncounter, CmRDT, CvRDT, Dot, GCounter, GSet, MVReg, Orswot, PNCounter, VClock};
+
+use std::fmt::Debug;
+
+const REPLICAS: u8 = 4;
+
+/// Every ordering of the given items.
+fn permutations<T: Clone>(items: &[T]) -> Vec<Vec<T>> {
+    if items.is_empty() {
+        return vec![Vec::new()];
+    }
+
+    let mut perms = Vec::new();
+    for i in 0..items.len() {
+        let mut rest = items.to_vec();
+        let item = rest.remove(i);
+        for mut perm in permutations(&rest) {
+            perm.insert(0, item.clone());
+            perms.push(perm);
+        }
+    }
+    perms
+}
+
+/// Simulate a handful of replicas, each editing under its own actor and
+/// occasionally syncing with another replica, returning the final states.
+fn simulate<T, F>(plan: &[(u8, u8, u8)], edit: F) -> Vec<T>
+where
+    T: CvRDT + Clone + Default,
+    F: Fn(&mut T, u8, u8),
+{
+    let mut replicas: Vec<T> = (0..REPLICAS).map(|_| T::default()).collect();
+    for (replica, action, val) in plan.iter().cloned() {
+        let actor = replica % REPLICAS;
+        if action % 3 == 0 {
+            let peer = replicas[(val % REPLICAS) as usize].clone();
+            replicas[actor as usize].merge(peer);
+        } else {
+            edit(&mut replicas[actor as usize], actor, val);
+        }
+    }
+    replicas
+}
+
+/// Merge the states in every possible order and check we always get the same result.
+fn merge_order_is_irrelevant<T>(states: Vec<T>) -> bool
+where
+    T: CvRDT + Clone + Default + PartialEq + Debug,
+{
+    let mut result: Option<T> = None;
+    for perm in permutations(&states) {
+        let mut merged = T::default();
+        for state in perm {
+            merged.merge(state);
+        }
+
+        match &result {
+            Some(prev) if prev != &merged => {
+                println!("states: {:?}", states);
+                println!("expected: {:?}", prev);
+                println!("merged: {:?}", merged);
+                return false;
+            }
+            Some(_) => (),
+            None => result = Some(merged),
+        }
+    }
+    true
+}
+
+/// Merge must be idempotent, commutative and associative.
+fn cvrdt_laws<T>(a: T, b: T, c: T) -> bool
+where
+    T: CvRDT + Clone + Default + PartialEq + Debug,
+{
+    let mut a_a = a.clone();
+    a_a.merge(a.clone());
+
+    a_a == a && merge_order_is_irrelevant(vec![a, b, c])
+}
+
+/// Applying ops must be idempotent, and the order they are applied in must not matter.
+fn cmrdt_laws<T>(state: T, ops: Vec<T::Op>) -> bool
+where
+    T: CmRDT + Clone + PartialEq + Debug,
+    T::Op: Clone,
+{
+    let mut in_order = state.clone();
+    let mut twice = state.clone();
+    for op in ops.iter().cloned() {
+        in_order.apply(op.clone());
+        twice.apply(op.clone());
+        twice.apply(op);
+    }
+
+    let mut reversed = state;
+    for op in ops.into_iter().rev() {
+        reversed.apply(op);
+    }
+
+    in_order == twice && in_order == reversed
+}
+
+quickcheck! {
+    fn prop_vclock_merge_order(plan: Vec<(u8, u8, u8)>) -> bool {
+        merge_order_is_irrelevant(simulate(&plan, |clock: &mut VClock<u8>, actor, _| {
+            clock.apply(clock.inc(actor))
+        }))
+    }
+
+    fn prop_gcounter_merge_order(plan: Vec<(u8, u8, u8)>) -> bool {
+        merge_order_is_irrelevant(simulate(&plan, |counter: &mut GCounter<u8>, actor, _| {
+            counter.apply(counter.inc(actor))
+        }))
+    }
+
+    fn prop_pncounter_merge_order(plan: Vec<(u8, u8, u8)>) -> bool {
+        merge_order_is_irrelevant(simulate(&plan, |counter: &mut PNCounter<u8>, actor, val| {
+            if val % 2 == 0 {
+                counter.apply(counter.inc(actor))
+            } else {
+                counter.apply(counter.dec(actor))
+            }
+        }))
+    }
+
+    fn prop_gcounter_cvrdt_laws(a: GCounter<u8>, b: GCounter<u8>, c: GCounter<u8>) -> bool {
+        cvrdt_laws(a, b, c)
+    }
+
+    fn prop_gcounter_cmrdt_laws(counter: GCounter<u8>, ops: Vec<Dot<u8>>) -> bool {
+        cmrdt_laws(counter, ops)
+    }
+
+    fn prop_pncounter_cvrdt_laws(a: PNCounter<u8>, b: PNCounter<u8>, c: PNCounter<u8>) -> bool {
+        cvrdt_laws(a, b, c)
+    }
+
+    fn prop_pncounter_cmrdt_laws(counter: PNCounter<u8>, ops: Vec<pncounter::Op<u8>>) -> bool {
+        cmrdt_laws(counter, ops)
+    }
+
+    fn prop_gset_merge_order(plan: Vec<(u8, u8, u8)>) -> bool {
+        merge_order_is_irrelevant(simulate(&plan, |set: &mut GSet<u8>, _, val| {
+            set.insert(val)
+        }))
+    }
+
+    fn prop_mvreg_merge_order(plan: Vec<(u8, u8, u8)>) -> bool {
+        merge_order_is_irrelevant(simulate(&plan, |reg: &mut MVReg<u8, u8>, actor, val| {
+            reg.apply(reg.write(val, reg.read().derive_add_ctx(actor)))
+        }))
+    }
+
+    fn prop_orswot_merge_order(plan: Vec<(u8, u8, u8)>) -> bool {
+        merge_order_is_irrelevant(simulate(&plan, |set: &mut Orswot<u8, u8>, actor, val| {
+            let member = val % 8;
+            if val % 2 == 0 {
+                set.apply(set.add(member, set.read().derive_add_ctx(actor)))
+            } else {
+                set.apply(set.rm(member, set.contains(&member).derive_rm_ctx()))
+            }
+        }))
+    }
+
+    // LSeq only replicates through ops, so instead of merging states we apply
+    // each site's ops as a batch, with the batches in every possible order.
+    fn prop_lseq_apply_order(plan: Vec<(u8, u8, u8)>) -> bool {
+        let mut sites: Vec<LSeq<u8, u8>> = (0..REPLICAS).map(LSeq::new).collect();
+        let mut batches: Vec<Vec<lseq::Op<u8, u8>>> = (0..REPLICAS).map(|_| Vec::new()).collect();
+        for (site_ix, ix, val) in plan {
+            let site_ix = (site_ix % REPLICAS) as usize;
+            let site = &mut sites[site_ix];
+            let op = if val % 3 == 0 && !site.is_empty() {
+                site.delete_index(ix as usize % site.len()).unwrap()
+            } else {
+                site.insert_index(ix as usize % (site.len() + 1), val)
+            };
+            batches[site_ix].push(op);
+        }
+
+        let mut result: Option<Vec<u8>> = None;
+        for perm in permutations(&batches) {
+            let mut replica = LSeq::new(REPLICAS);
+            for op in perm.into_iter().flatten() {
+                replica.apply(op);
+            }
+            let content: Vec<u8> = replica.iter().cloned().collect();
+
+            match &result {
+                Some(prev) if prev != &content => return false,
+                Some(_) => (),
+                None => result = Some(content),
+            }
+        }
+        true
+    }
+}
+
+#[test]
+fn test_permutations() {
+    assert_eq!(permutations::<u8>(&[]), vec![Vec::<u8>::new()]);
+    assert_eq!(
+        permutations(&[1, 2, 3]),
+        vec![
+            vec![1, 2, 3],
+            vec![1, 3, 2],
+            vec![2, 1, 3],
+            vec![2, 3, 1],
+            vec![3, 1, 2],
+            vec![3, 2, 1],
+        ]
+    );
+}
@@ -1,6 +1,16 @@
-use crdts::{mvreg::Op, *};
+use crdts::{
+    mvreg::{
+        BranchInfo, CasError, LegacyState, MalformedOp, Op, Resolution, SizeError, State,
+        Timestamped,
+    },
+    *,
+};
 
 use quickcheck::TestResult;
+use std::cmp::Ordering;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashSet;
+use std::hash::{Hash, Hasher};
 
 #[derive(Debug, Clone)]
 struct TestReg {
@@ -33,6 +43,155 @@ fn test_write_should_not_mutate_reg() {
     assert_eq!(reg.read().add_clock, VClock::from(Dot::new("A", 1)));
 }
 
+#[test]
+fn test_try_set_respects_size_budget() {
+    let mut reg = MVReg::new();
+    let max_bytes = 16;
+
+    let big = "a value that will not fit in the budget".to_string();
+    let ctx = reg.read().derive_add_ctx("A");
+    assert_eq!(
+        reg.try_set(big, ctx, max_bytes),
+        Err(SizeError::Oversized {
+            size: 47,
+            max_bytes
+        })
+    );
+
+    let small = "fits".to_string();
+    let ctx = reg.read().derive_add_ctx("A");
+    let op = reg.try_set(small.clone(), ctx, max_bytes).unwrap();
+    reg.apply(op);
+    assert_eq!(reg.read().val, vec![small]);
+}
+
+#[test]
+fn test_cas_on_empty_register() {
+    let mut reg = MVReg::new();
+
+    let ctx = reg.read().derive_add_ctx("A");
+    let op = reg.cas(&VClock::new(), 1, ctx).unwrap();
+    reg.apply(op);
+    assert_eq!(reg.read().val, vec![1]);
+
+    // the register is no longer empty, so expecting an empty clock is now stale
+    let ctx = reg.read().derive_add_ctx("A");
+    assert_eq!(
+        reg.cas(&VClock::new(), 2, ctx),
+        Err(CasError {
+            current: VClock::from(Dot::new("A", 1))
+        })
+    );
+}
+
+#[test]
+fn test_cas_succeeds_when_clock_matches() {
+    let mut reg = MVReg::new();
+    reg.apply(reg.write(1, reg.read().derive_add_ctx("A")));
+
+    let read = reg.read();
+    let expected = read.add_clock.clone();
+    let op = reg.cas(&expected, 2, read.derive_add_ctx("B")).unwrap();
+
+    // cas doesn't touch the register until the op is applied
+    assert_eq!(reg.read().val, vec![1]);
+    reg.apply(op);
+    assert_eq!(reg.read().val, vec![2]);
+}
+
+#[test]
+fn test_cas_fails_on_stale_expected_clock() {
+    let mut reg = MVReg::new();
+    reg.apply(reg.write(1, reg.read().derive_add_ctx("A")));
+    let stale = reg.read();
+
+    // a concurrent writer gets in first
+    reg.apply(reg.write(2, reg.read().derive_add_ctx("B")));
+
+    let current = reg.read().add_clock;
+    assert_eq!(
+        reg.cas(&stale.add_clock, 3, stale.derive_add_ctx("C")),
+        Err(CasError { current })
+    );
+    assert_eq!(reg.read().val, vec![2]);
+}
+
+#[test]
+fn test_conflict_queries() {
+    let mut r1 = MVReg::new();
+    assert_eq!(r1.value_count(), 0);
+    assert!(!r1.is_conflicted());
+
+    r1.apply(r1.write(1, r1.read().derive_add_ctx("A")));
+    assert_eq!(r1.value_count(), 1);
+    assert!(!r1.is_conflicted());
+
+    let mut r2 = MVReg::new();
+    r2.apply(r2.write(2, r2.read().derive_add_ctx("B")));
+    r1.merge(r2);
+    assert_eq!(r1.value_count(), 2);
+    assert!(r1.is_conflicted());
+
+    let conflicts = r1.conflicts();
+    assert_eq!(conflicts.add_clock, r1.read().add_clock);
+    assert_eq!(
+        conflicts.val,
+        vec![
+            (VClock::from(Dot::new("A", 1)), &1),
+            (VClock::from(Dot::new("B", 1)), &2)
+        ]
+    );
+}
+
+#[test]
+fn test_explain_lists_provenance_of_each_branch() {
+    let mut reg = MVReg::new();
+    let writes = vec![
+        (vec![Dot::new("A", 2), Dot::new("C", 1)], "alpha"),
+        (vec![Dot::new("B", 1)], "beta"),
+        (vec![Dot::new("C", 3)], "gamma"),
+    ];
+    for (dots, val) in writes {
+        reg.apply(Op::Put {
+            clock: dots.into_iter().collect(),
+            val,
+        });
+    }
+
+    assert_eq!(
+        reg.explain(),
+        vec![
+            BranchInfo {
+                value: &"alpha",
+                actors: vec![("A", 2), ("C", 1)],
+                concurrent: true,
+            },
+            BranchInfo {
+                value: &"beta",
+                actors: vec![("B", 1)],
+                concurrent: true,
+            },
+            BranchInfo {
+                value: &"gamma",
+                actors: vec![("C", 3)],
+                concurrent: true,
+            },
+        ]
+    );
+
+    // a write that has seen every branch resolves the conflict
+    reg.apply(reg.write("delta", reg.read().derive_add_ctx("B")));
+    assert_eq!(
+        reg.explain(),
+        vec![BranchInfo {
+            value: &"delta",
+            actors: vec![("A", 2), ("B", 2), ("C", 3)],
+            concurrent: false,
+        }]
+    );
+    assert!(MVReg::<u8, u8>::new().explain().is_empty());
+}
+
 #[test]
 fn test_concurrent_update_with_same_value_dont_collapse_on_merge() {
     // this is important to prevent because it breaks commutativity
@@ -104,6 +263,537 @@ fn test_op_commute_quickcheck1() {
     assert_eq!(reg1, reg2);
 }
 
+#[test]
+fn test_stale_put_does_not_resurrect_forgotten_value() {
+    let mut reg = MVReg::new();
+    let stale_op = reg.write(1, reg.read().derive_add_ctx("A"));
+    reg.apply(stale_op.clone());
+
+    reg.forget(&reg.read().add_clock);
+    assert!(reg.read().val.is_empty());
+
+    // a late redelivery of the forgotten put is ignored
+    reg.apply(stale_op);
+    assert!(reg.read().val.is_empty());
+
+    // but puts with new history still go through
+    reg.apply(reg.write(2, reg.read().derive_add_ctx("B")));
+    assert_eq!(reg.read().val, vec![2]);
+}
+
+#[test]
+fn test_purge_only_empties_local_replica() {
+    let mut reg = MVReg::new();
+    reg.apply(reg.write(1, reg.read().derive_add_ctx("A")));
+    let peer = reg.clone();
+
+    reg.purge();
+    assert_eq!(reg, MVReg::new());
+    assert!(reg.read().val.is_empty());
+
+    // the purge is not replicated, merging with a peer brings the value back
+    reg.merge(peer);
+    assert_eq!(reg.read().val, vec![1]);
+}
+
+#[test]
+fn test_retain_values() {
+    let mut reg = MVReg::new();
+    reg.apply(reg.write(1, MVReg::<u8, &str>::new().read().derive_add_ctx("A")));
+    reg.apply(reg.write(2, MVReg::<u8, &str>::new().read().derive_add_ctx("B")));
+    assert_eq!(reg.read().val, vec![1, 2]);
+
+    reg.retain_values(|val| val % 2 == 0);
+    assert_eq!(reg.read().val, vec![2]);
+    assert_eq!(reg.read().add_clock, VClock::from(Dot::new("B", 1)));
+}
+
+#[test]
+fn test_compact_ops_drops_dominated_puts() {
+    let mut reg = MVReg::new();
+    let first = reg.write(1, reg.read().derive_add_ctx("A"));
+    reg.apply(first.clone());
+    let second = reg.write(2, reg.read().derive_add_ctx("A"));
+    let concurrent = MVReg::new().write(3, MVReg::<u8, &str>::new().read().derive_add_ctx("B"));
+
+    let log = vec![first, second.clone(), concurrent.clone()];
+    let compacted = MVReg::compact_ops(log.clone());
+    assert_eq!(compacted, vec![second, concurrent]);
+
+    let mut replayed_log = MVReg::new();
+    let mut replayed_compacted = MVReg::new();
+    for op in log {
+        replayed_log.apply(op);
+    }
+    for op in compacted {
+        replayed_compacted.apply(op);
+    }
+    assert_eq!(replayed_log, replayed_compacted);
+    assert_eq!(replayed_compacted.read().val, vec![2, 3]);
+}
+
+#[test]
+fn test_bottom() {
+    let bottom: MVReg<u8, &str> = MVReg::bottom();
+    assert!(bottom.is_bottom());
+
+    let mut reg = MVReg::new();
+    reg.apply(reg.write(1, reg.read().derive_add_ctx("A")));
+    assert!(!reg.is_bottom());
+
+    let snapshot = reg.clone();
+    reg.merge(MVReg::bottom());
+    assert_eq!(reg, snapshot);
+}
+
+#[test]
+fn test_merge_all_matches_sequential_merges() {
+    let mut r1 = MVReg::new();
+    let mut r2 = MVReg::new();
+    let mut r3 = MVReg::new();
+
+    r1.apply(r1.write(1, r1.read().derive_add_ctx("A")));
+    r2.apply(r2.write(2, r2.read().derive_add_ctx("B")));
+    r3.merge(r1.clone());
+    r3.apply(r3.write(3, r3.read().derive_add_ctx("C")));
+
+    let mut sequential = MVReg::new();
+    sequential.merge(r1.clone());
+    sequential.merge(r2.clone());
+    sequential.merge(r3.clone());
+
+    let mut all_at_once = MVReg::new();
+    all_at_once.merge_all(&[r1, r2, r3]);
+
+    assert_eq!(all_at_once, sequential);
+    assert_eq!(all_at_once.read().val, vec![2, 3]);
+}
+
+/// Sync two replicas through state and then drop the history they share
+fn sync_and_forget<A: Actor, T: Crdt<A>>(a: &mut T, b: &mut T, stable: &VClock<A>) {
+    a.merge(b.clone());
+    b.merge(a.clone());
+    a.forget(stable);
+    b.forget(stable);
+}
+
+#[test]
+fn test_mvreg_is_crdt() {
+    let mut r1 = MVReg::new();
+    let mut r2 = MVReg::new();
+    r1.apply(r1.write(1, r1.read().derive_add_ctx("A")));
+    r2.apply(r2.write(2, r2.read().derive_add_ctx("B")));
+
+    sync_and_forget(&mut r1, &mut r2, &VClock::from(Dot::new("A", 1)));
+
+    assert_eq!(r1, r2);
+    assert_eq!(r1.read().val, vec![2]);
+}
+
+#[test]
+fn test_read_iter_matches_read() {
+    let mut reg = MVReg::new();
+    reg.apply(reg.write(1, MVReg::<u8, _>::new().read().derive_add_ctx("A")));
+    reg.apply(reg.write(2, MVReg::<u8, _>::new().read().derive_add_ctx("B")));
+
+    let read = reg.read();
+    let read_iter = reg.read_iter();
+    assert_eq!(read_iter.add_clock, read.add_clock);
+    assert_eq!(read_iter.rm_clock, read.rm_clock);
+    assert_eq!(read_iter.val.cloned().collect::<Vec<_>>(), read.val);
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Stamped {
+    time: u64,
+    val: &'static str,
+}
+
+impl Timestamped for Stamped {
+    type Time = u64;
+
+    fn timestamp(&self) -> u64 {
+        self.time
+    }
+}
+
+#[test]
+fn test_latest_by_time() {
+    let mut reg = MVReg::new();
+    assert_eq!(reg.latest_by_time(), None);
+
+    let older = Stamped {
+        time: 10,
+        val: "older",
+    };
+    let newer = Stamped {
+        time: 20,
+        val: "newer",
+    };
+    let ctx = reg.read_ctx();
+    reg.apply(reg.write(newer.clone(), ctx.derive_add_ctx("A")));
+    reg.apply(reg.write(older, ctx.derive_add_ctx("B")));
+
+    assert_eq!(reg.read().val.len(), 2);
+    assert_eq!(reg.latest_by_time(), Some(&newer));
+}
+
+#[test]
+fn test_latest_by_time_ties_are_deterministic() {
+    let a = Stamped { time: 10, val: "a" };
+    let b = Stamped { time: 10, val: "b" };
+    let mut r1 = MVReg::new();
+    let mut r2 = MVReg::new();
+
+    let op_a = r1.write(a, r1.read_ctx().derive_add_ctx("A"));
+    let op_b = r2.write(b.clone(), r2.read_ctx().derive_add_ctx("B"));
+    r1.apply(op_a.clone());
+    r1.apply(op_b.clone());
+    r2.apply(op_b);
+    r2.apply(op_a);
+
+    // {B: 1} sorts after {A: 1}
+    assert_eq!(r1.latest_by_time(), Some(&b));
+    assert_eq!(r2.latest_by_time(), Some(&b));
+}
+
+#[test]
+fn test_merge_ref_matches_merge() {
+    let mut r1 = MVReg::new();
+    let mut r2 = MVReg::new();
+    r1.apply(r1.write(1, r1.read().derive_add_ctx("A")));
+    r2.apply(r2.write(2, r2.read().derive_add_ctx("B")));
+    r2.apply(r2.write(3, r2.read().derive_add_ctx("B")));
+    let mut r3 = r1.clone();
+    r3.merge(r2.clone());
+    r3.apply(r3.write(4, r3.read().derive_add_ctx("C")));
+
+    for (a, b) in [(&r1, &r2), (&r2, &r1), (&r1, &r3), (&r3, &r2), (&r3, &r3)].iter() {
+        let mut merged = (*a).clone();
+        merged.merge((*b).clone());
+        let mut merged_ref = (*a).clone();
+        merged_ref.merge_ref(b);
+
+        assert_eq!(merged_ref, merged);
+        assert_eq!(merged_ref.read().add_clock, merged.read().add_clock);
+    }
+}
+
+/// Apply an op only if `validate_op` accepts it
+fn guarded_apply(reg: &mut MVReg<u8, u8>, op: Op<u8, u8>) {
+    if reg.validate_op(&op).is_ok() {
+        reg.apply(op);
+    }
+}
+
+#[test]
+fn test_validate_op_rejects_conflicting_put() {
+    let mut reg = MVReg::new();
+    let ctx = reg.read().derive_add_ctx(1);
+    let op = reg.write(1, ctx.clone());
+    let conflicting = reg.write(2, ctx);
+
+    assert_eq!(reg.validate_op(&conflicting), Ok(()));
+    reg.apply(op.clone());
+
+    assert_eq!(reg.validate_op(&op), Ok(()));
+    assert_eq!(
+        reg.validate_op(&conflicting),
+        Err(MalformedOp::ConflictingPut)
+    );
+
+    // a guarded apply rejects the put instead of storing a second value under its clock
+    guarded_apply(&mut reg, conflicting);
+    assert_eq!(reg.read().val, vec![1]);
+}
+
+#[test]
+fn test_merge_with_equal_clocks_is_idempotent() {
+    let mut r1 = MVReg::new();
+    let mut r2 = MVReg::new();
+    r1.apply(r1.write(23, r1.read().derive_add_ctx(1)));
+    r2.apply(r2.write(23, r2.read().derive_add_ctx(2)));
+    let mut merged = r1.clone();
+    merged.merge(r2.clone());
+
+    // an overlapping state shares an identical clock with the merged register
+    for _ in 0..2 {
+        merged.merge(r1.clone());
+        merged.merge_ref(&r2);
+        merged.merge_all(&[r1.clone(), r2.clone()]);
+        merged.merge_delta(r2.delta(&VClock::new()));
+    }
+    assert_eq!(merged.read().val, vec![23, 23]);
+    assert_eq!(merged.read_with_clocks().val.len(), 2);
+}
+
+#[test]
+fn test_merge_keeps_conflicting_puts_in_any_order() {
+    // two malformed puts under the same clock, delivered to different replicas
+    let ctx = MVReg::<u8, u8>::new().read().derive_add_ctx(1);
+    let mut r1 = MVReg::new();
+    let mut r2 = MVReg::new();
+    r1.apply(r1.write(1, ctx.clone()));
+    r2.apply(r2.write(2, ctx));
+
+    let mut merged_12 = r1.clone();
+    merged_12.merge(r2.clone());
+    let mut merged_21 = r2.clone();
+    merged_21.merge_ref(&r1);
+    let mut merged_all = MVReg::new();
+    merged_all.merge_all(&[r1.clone(), r2.clone(), r1]);
+
+    // like `apply`, merging keeps both values rather than the first one it sees
+    assert_eq!(merged_12.value_count(), 2);
+    assert_eq!(merged_12, merged_21);
+    assert_eq!(merged_12, merged_all);
+
+    merged_12.merge(merged_21);
+    assert_eq!(merged_12.value_count(), 2);
+}
+
+#[test]
+fn test_apply_all_is_all_or_nothing() {
+    let mut site = MVReg::new();
+    let first = site.write(1, site.read().derive_add_ctx(1));
+    site.apply(first.clone());
+    let second = site.write(2, site.read().derive_add_ctx(1));
+    site.apply(second.clone());
+
+    let mut reg: MVReg<u8, u8> = MVReg::new();
+    let before = bincode::serialize(&reg).unwrap();
+
+    // the conflict is only with a put earlier in the same batch
+    let conflicting = Op::Put {
+        clock: second.clock().clone(),
+        val: 3,
+    };
+    let batch = [first.clone(), second.clone(), conflicting];
+    assert_eq!(reg.apply_all(&batch), Err((2, MalformedOp::ConflictingPut)));
+    assert_eq!(bincode::serialize(&reg).unwrap(), before);
+
+    let empty = Op::Put {
+        clock: VClock::new(),
+        val: 4,
+    };
+    assert_eq!(
+        reg.apply_all(&[first.clone(), empty]),
+        Err((1, MalformedOp::EmptyClock))
+    );
+    assert_eq!(bincode::serialize(&reg).unwrap(), before);
+
+    assert_eq!(reg.apply_all(&[first, second]), Ok(()));
+    assert_eq!(reg, site);
+}
+
+#[test]
+fn test_apply_all_checks_puts_against_values_left_by_earlier_clears() {
+    let mut reg: MVReg<&str, u8> = MVReg::new();
+    let put_a = reg.write("a", reg.read_ctx().derive_add_ctx(1));
+    let c1 = put_a.clock().clone();
+    reg.apply(put_a);
+
+    let put_b = Op::Put {
+        clock: c1.clone(),
+        val: "b",
+    };
+    assert_eq!(reg.validate_op(&put_b), Err(MalformedOp::ConflictingPut));
+
+    // applied one by one, the clear drops "a" and the put is then stale but valid
+    let batch = [Op::Clear { clock: c1 }, put_b];
+    let mut sequential = reg.clone();
+    for op in batch.iter().cloned() {
+        assert_eq!(sequential.validate_op(&op), Ok(()));
+        sequential.apply(op);
+    }
+
+    assert_eq!(reg.apply_all(&batch), Ok(()));
+    assert_eq!(reg, sequential);
+    assert!(reg.read().val.is_empty());
+}
+
+#[test]
+fn test_validate_op_rejects_empty_clock() {
+    let mut reg: MVReg<u8, u8> = MVReg::new();
+    let op = Op::Put {
+        clock: VClock::new(),
+        val: 1,
+    };
+
+    assert_eq!(reg.validate_op(&op), Err(MalformedOp::EmptyClock));
+    reg.apply(op);
+    assert!(reg.is_bottom());
+}
+
+#[test]
+fn test_op_accessors() {
+    let reg = MVReg::with_value(1, "A");
+    let op = reg.write(2, reg.read().derive_add_ctx("B"));
+
+    assert_eq!(op.value(), Some(&2));
+    assert_eq!(
+        op.clock(),
+        &vec![Dot::new("A", 1), Dot::new("B", 1)]
+            .into_iter()
+            .collect()
+    );
+}
+
+#[test]
+fn test_concurrent_write_wins_over_remove() {
+    let mut r1 = MVReg::with_value(1, "A");
+    let mut r2 = r1.clone();
+
+    // r1 removes the value while r2 concurrently overwrites it
+    let rm_clock = r1.rm_clock();
+    r1.apply_rm(&rm_clock);
+    assert!(r1.read().val.is_empty());
+
+    let write = r2.write(2, r2.read().derive_add_ctx("B"));
+    r2.apply(write.clone());
+
+    r1.apply(write);
+    r2.apply_rm(&rm_clock);
+
+    assert_eq!(r1, r2);
+    assert_eq!(r1.read().val, vec![2]);
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+struct ProfileV1 {
+    name: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct ProfileV2 {
+    name: String,
+    verified: bool,
+}
+
+#[test]
+fn test_migrate_persisted_register() {
+    let mut reg = MVReg::new();
+    let ctx = reg.read_ctx();
+    let alice = ProfileV1 {
+        name: "alice".into(),
+    };
+    let bob = ProfileV1 { name: "bob".into() };
+    reg.apply(reg.write(alice, ctx.derive_add_ctx("A")));
+    reg.apply(reg.write(bob, ctx.derive_add_ctx("B")));
+
+    let persisted = bincode::serialize(&reg).unwrap();
+    let restored: MVReg<ProfileV1, &str> = bincode::deserialize(&persisted).unwrap();
+    assert_eq!(restored.schema_version(), 0);
+
+    let migrated = restored
+        .migrate(2, |v1| ProfileV2 {
+            name: v1.name,
+            verified: false,
+        })
+        .unwrap();
+
+    assert_eq!(migrated.schema_version(), 2);
+    assert_eq!(migrated.read().add_clock, reg.read().add_clock);
+    assert_eq!(
+        migrated.read().val,
+        vec![
+            ProfileV2 {
+                name: "alice".into(),
+                verified: false
+            },
+            ProfileV2 {
+                name: "bob".into(),
+                verified: false
+            },
+        ]
+    );
+
+    // the version survives persistence, so the migration is only run once
+    let persisted = bincode::serialize(&migrated.clone().map_values(|v2| v2.name)).unwrap();
+    let restored: MVReg<String, &str> = bincode::deserialize(&persisted).unwrap();
+    assert_eq!(restored.schema_version(), 2);
+    assert!(restored.migrate(2, |name| name).is_err());
+}
+
+#[test]
+fn test_decode_registers_in_the_4x_format() {
+    // bincode bytes of a crdts 4.x `MVReg<u8, u8>` holding 5 written at {1: 2}
+    let old_bytes = [
+        1, 0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0, 1, 2, 0, 0, 0, 0, 0, 0, 0, 5,
+    ];
+    let expected = MVReg::<u8, u8>::from(State {
+        vals: vec![(VClock::from(Dot::new(1, 2)), 5)],
+        forgotten: VClock::new(),
+        schema_version: 0,
+    });
+
+    // bincode isn't self-describing, so the old layout has to be asked for
+    assert!(bincode::deserialize::<MVReg<u8, u8>>(&old_bytes).is_err());
+    let legacy: LegacyState<u8, VClock<u8>> = bincode::deserialize(&old_bytes).unwrap();
+    assert_eq!(MVReg::from(legacy), expected);
+
+    // JSON is, the missing fields take their defaults
+    let old_json = r#"{"vals":[[{"dots":{"1":2}},5]]}"#;
+    let from_json: MVReg<u8, u8> = serde_json::from_str(old_json).unwrap();
+    assert_eq!(from_json, expected);
+    assert_eq!(from_json.read().add_clock, VClock::from(Dot::new(1, 2)));
+}
+
+#[test]
+fn test_with_value() {
+    let reg = MVReg::with_value(7, "A");
+
+    assert_eq!(reg.read().val, vec![7]);
+    assert_eq!(reg.read().add_clock, VClock::from(Dot::new("A", 1)));
+    assert_eq!(reg, {
+        let mut r = MVReg::new();
+        r.apply(r.write(7, r.read().derive_add_ctx("A")));
+        r
+    });
+}
+
+#[test]
+fn test_regs_and_clocks_as_hash_set_keys() {
+    let a = VClock::from(Dot::new(1, 1));
+    let b = VClock::from(Dot::new(2, 1));
+    let ab = MVReg::from_concurrent(vec![(a.clone(), 10u8), (b.clone(), 20)]).unwrap();
+    let ba = MVReg::from_concurrent(vec![(b.clone(), 20u8), (a.clone(), 10)]).unwrap();
+
+    let regs: HashSet<MVReg<u8, u8>> = vec![ab, ba, MVReg::new()].into_iter().collect();
+    assert_eq!(regs.len(), 2);
+
+    let clocks: HashSet<VClock<u8>> = vec![a.clone(), b, a].into_iter().collect();
+    assert_eq!(clocks.len(), 2);
+}
+
+#[test]
+fn test_from_concurrent_three_way_conflict() {
+    let reg: MVReg<u8, u8> = MVReg::from_concurrent(vec![
+        (Dot::new(1, 1).into(), 1),
+        (Dot::new(2, 1).into(), 2),
+        (Dot::new(3, 1).into(), 3),
+    ])
+    .unwrap();
+
+    assert_eq!(reg.read().val.len(), 3);
+    assert_eq!(reg.read().val, vec![1, 2, 3]);
+}
+
+#[test]
+fn test_from_concurrent_rejects_ordered_clocks() {
+    let earlier: VClock<u8> = Dot::new(1, 1).into();
+    let later: VClock<u8> = vec![Dot::new(1, 1), Dot::new(2, 1)].into_iter().collect();
+
+    assert_eq!(
+        MVReg::from_concurrent(vec![(earlier.clone(), 1), (later, 2)]),
+        None
+    );
+    assert_eq!(
+        MVReg::from_concurrent(vec![(earlier.clone(), 1), (earlier, 2)]),
+        None
+    );
+}
+
 fn ops_are_not_compatible(opss: &[&Vec<(u8, u8)>]) -> bool {
     // We need to make sure that we never insert two different values with
     // the same actor version.
@@ -115,7 +805,7 @@ fn ops_are_not_compatible(opss: &[&Vec<(u8, u8)>]) -> bool {
                 a_clock.apply(a_clock.inc(*a_actor));
                 b_clock.apply(b_clock.inc(*b_actor));
 
-                if b_clock.get(&a_actor) == a_clock.get(&a_actor) {
+                if b_clock.get(a_actor) == a_clock.get(a_actor) {
                     // this check is a bit broad as it's not a failure
                     // to insert the same value with the same actor version
                     // but for simplicity we reject those ops as well
@@ -127,6 +817,12 @@ fn ops_are_not_compatible(opss: &[&Vec<(u8, u8)>]) -> bool {
     false
 }
 
+fn hash_of<T: Hash>(t: &T) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    t.hash(&mut hasher);
+    hasher.finish()
+}
+
 fn build_test_reg(prim_ops: Vec<(u8, u8)>) -> TestReg {
     let mut reg = MVReg::default();
     let mut ops = Vec::new();
@@ -140,6 +836,26 @@ fn build_test_reg(prim_ops: Vec<(u8, u8)>) -> TestReg {
 }
 
 quickcheck! {
+    fn prop_guarded_apply_accepts_well_formed_ops(r1_prim_ops: Vec<(u8, u8)>, r2_prim_ops: Vec<(u8, u8)>) -> TestResult {
+        if ops_are_not_compatible(&[&r1_prim_ops, &r2_prim_ops]) {
+            return TestResult::discard();
+        }
+
+        let r1 = build_test_reg(r1_prim_ops);
+        let r2 = build_test_reg(r2_prim_ops);
+
+        // guarded application in either order converges to the unguarded result
+        let mut r1_then_r2 = MVReg::new();
+        r1.ops.iter().chain(r2.ops.iter()).cloned().for_each(|op| guarded_apply(&mut r1_then_r2, op));
+        let mut r2_then_r1 = MVReg::new();
+        r2.ops.iter().chain(r1.ops.iter()).cloned().for_each(|op| guarded_apply(&mut r2_then_r1, op));
+
+        let mut merged = r1.reg;
+        merged.merge(r2.reg);
+
+        TestResult::from_bool(r1_then_r2 == r2_then_r1 && r1_then_r2 == merged)
+    }
+
     fn prop_set_with_ctx_from_read(r_ops: Vec<(u8, u8)>, a: u8) -> bool {
         let mut reg = build_test_reg(r_ops).reg;
         let write_ctx = reg.read().derive_add_ctx(a);
@@ -149,34 +865,135 @@ quickcheck! {
         next_read_ctx.val == vec![23]
     }
 
+    fn prop_merge_agrees_with_partial_ord(
+        r1_ops: Vec<(u8, u8)>,
+        r2_ops: Vec<(u8, u8)>,
+        later_ops: Vec<(u8, u8)>
+    ) -> TestResult {
+        if ops_are_not_compatible(&[&r1_ops, &r2_ops]) {
+            return TestResult::discard();
+        }
+        let r1 = build_test_reg(r1_ops).reg;
+        let r2 = build_test_reg(r2_ops).reg;
+
+        // a descendant of r1 dominates it, so merging r1 in changes nothing
+        let mut descendant = r1.clone();
+        for (val, actor) in later_ops {
+            descendant.apply(descendant.write(val, descendant.read_ctx().derive_add_ctx(actor)));
+        }
+        assert!(descendant >= r1);
+        let mut merged = descendant.clone();
+        merged.merge(r1.clone());
+        assert_eq!(merged, descendant);
+
+        let mut merged = r1.clone();
+        merged.merge(r2.clone());
+        match r1.partial_cmp(&r2) {
+            Some(Ordering::Greater) | Some(Ordering::Equal) => assert_eq!(merged, r1),
+            Some(Ordering::Less) => assert_eq!(merged, r2),
+            None => assert!(merged > r1 && merged > r2),
+        }
+        TestResult::passed()
+    }
+
     fn prop_merge_idempotent(r_ops: Vec<(u8, u8)>) -> bool {
         let mut r = build_test_reg(r_ops).reg;
         let r_snapshot = r.clone();
 
-        r.merge(r_snapshot.clone());
+        r.merge(r_snapshot.clone());
+
+        assert_eq!(r, r_snapshot);
+        true
+    }
+
+    fn prop_merge_commutative(
+        r1_ops: Vec<(u8, u8)>,
+        r2_ops: Vec<(u8, u8)>
+    ) -> TestResult {
+        if ops_are_not_compatible(&[&r1_ops, &r2_ops]) {
+            return TestResult::discard();
+        }
+        let r1 = build_test_reg(r1_ops);
+        let r2 = build_test_reg(r2_ops);
+        let mut r1 = r1.reg;
+        let mut r2 = r2.reg;
+
+        let r1_snapshot = r1.clone();
+        r1.merge(r2.clone());
+        r2.merge(r1_snapshot);
+
+        assert_eq!(r1, r2);
+        TestResult::from_bool(true)
+    }
+
+    fn prop_equal_regs_hash_equal(
+        r1_ops: Vec<(u8, u8)>,
+        r2_ops: Vec<(u8, u8)>
+    ) -> TestResult {
+        if ops_are_not_compatible(&[&r1_ops, &r2_ops]) {
+            return TestResult::discard();
+        }
+        let mut r1 = build_test_reg(r1_ops).reg;
+        let mut r2 = build_test_reg(r2_ops).reg;
+
+        // merging in opposite directions stores the same values in different orders
+        let r1_snapshot = r1.clone();
+        r1.merge(r2.clone());
+        r2.merge(r1_snapshot);
+
+        assert_eq!(r1, r2);
+        TestResult::from_bool(hash_of(&r1) == hash_of(&r2))
+    }
+
+    fn prop_hash_ignores_value_order(vals: Vec<(u8, u8)>) -> TestResult {
+        // one clock per distinct actor keeps the values concurrent
+        let mut seen = HashSet::new();
+        let vals: Vec<(VClock<u8>, u8)> = vals
+            .into_iter()
+            .filter(|(actor, _)| seen.insert(*actor))
+            .map(|(actor, val)| (VClock::from(Dot::new(actor, 1)), val))
+            .collect();
+        let reversed = vals.iter().cloned().rev().collect();
+
+        let forward = MVReg::from_concurrent(vals).unwrap();
+        let backward = MVReg::from_concurrent(reversed).unwrap();
 
-        assert_eq!(r, r_snapshot);
-        true
+        assert_eq!(forward, backward);
+        TestResult::from_bool(hash_of(&forward) == hash_of(&backward))
     }
 
-    fn prop_merge_commutative(
+    fn prop_merge_ref_matches_merge(
         r1_ops: Vec<(u8, u8)>,
         r2_ops: Vec<(u8, u8)>
     ) -> TestResult {
         if ops_are_not_compatible(&[&r1_ops, &r2_ops]) {
             return TestResult::discard();
         }
-        let r1 = build_test_reg(r1_ops);
-        let r2 = build_test_reg(r2_ops);
-        let mut r1 = r1.reg;
-        let mut r2 = r2.reg;
+        let r1 = build_test_reg(r1_ops).reg;
+        let r2 = build_test_reg(r2_ops).reg;
 
-        let r1_snapshot = r1.clone();
-        r1.merge(r2.clone());
-        r2.merge(r1_snapshot);
+        let mut merged = r1.clone();
+        merged.merge(r2.clone());
+        let mut merged_ref = r1;
+        merged_ref.merge_ref(&r2);
 
-        assert_eq!(r1, r2);
-        TestResult::from_bool(true)
+        TestResult::from_bool(
+            merged_ref == merged && merged_ref.read().add_clock == merged.read().add_clock
+        )
+    }
+
+    fn prop_restore_snapshot(r_ops: Vec<(u8, u8)>, clear: bool) -> bool {
+        let mut reg = build_test_reg(r_ops).reg;
+        if clear {
+            reg.apply(reg.clear(reg.read().derive_rm_ctx()));
+        }
+        let state = reg.snapshot();
+        let restored = MVReg::restore(state.clone());
+
+        // the state is persisted in the same format as the register itself
+        restored == reg
+            && restored.read() == reg.read()
+            && bincode::serialize(&state).unwrap() == bincode::serialize(&reg).unwrap()
     }
 
     fn prop_merge_associative(
@@ -208,6 +1025,63 @@ quickcheck! {
         TestResult::from_bool(true)
     }
 
+    fn prop_merge_associative_preserves_read_order(
+        r1_ops: Vec<(u8, u8)>,
+        r2_ops: Vec<(u8, u8)>,
+        r3_ops: Vec<(u8, u8)>
+    ) -> TestResult {
+        if ops_are_not_compatible(&[&r1_ops, &r2_ops, &r3_ops]) {
+            return TestResult::discard();
+        }
+        let r1 = build_test_reg(r1_ops).reg;
+        let r2 = build_test_reg(r2_ops).reg;
+        let r3 = build_test_reg(r3_ops).reg;
+
+        // (r1 ^ r2) ^ r3
+        let mut left = r1.clone();
+        left.merge(r2.clone());
+        left.merge(r3.clone());
+
+        // r1 ^ (r2 ^ r3), without relying on commutativity
+        let mut r2_r3 = r2;
+        r2_r3.merge(r3);
+        let mut right = r1;
+        right.merge(r2_r3);
+
+        // equality is set-based, so also check the concurrent values come out in the same order
+        TestResult::from_bool(left == right && left.read().val == right.read().val)
+    }
+
+    fn prop_merge_with_bottom_is_nop(r_ops: Vec<(u8, u8)>) -> bool {
+        let mut r = build_test_reg(r_ops).reg;
+        let r_snapshot = r.clone();
+
+        r.merge(MVReg::bottom());
+        r == r_snapshot
+    }
+
+    fn prop_merge_all_same_as_sequential_merge(
+        r1_ops: Vec<(u8, u8)>,
+        r2_ops: Vec<(u8, u8)>,
+        r3_ops: Vec<(u8, u8)>
+    ) -> TestResult {
+        if ops_are_not_compatible(&[&r1_ops, &r2_ops, &r3_ops]) {
+            return TestResult::discard();
+        }
+        let r1 = build_test_reg(r1_ops).reg;
+        let r2 = build_test_reg(r2_ops).reg;
+        let r3 = build_test_reg(r3_ops).reg;
+
+        let mut sequential = r1.clone();
+        sequential.merge(r2.clone());
+        sequential.merge(r3.clone());
+
+        let mut all_at_once = r1;
+        all_at_once.merge_all(&[r2, r3]);
+
+        TestResult::from_bool(all_at_once == sequential)
+    }
+
     fn prop_forget(r_ops: Vec<(u8, u8)>) -> bool {
         let mut r = build_test_reg(r_ops).reg;
         let r_snapshot = r.clone();
@@ -224,6 +1098,66 @@ quickcheck! {
         true
     }
 
+    fn prop_forget_stable_history_commutes_with_merge(
+        r1_ops: Vec<(u8, u8)>,
+        r2_ops: Vec<(u8, u8)>,
+        bound: VClock<u8>
+    ) -> TestResult {
+        if ops_are_not_compatible(&[&r1_ops, &r2_ops]) {
+            return TestResult::discard();
+        }
+        let mut r1 = build_test_reg(r1_ops).reg;
+        let mut r2 = build_test_reg(r2_ops).reg;
+
+        // history is causally stable once every replica has seen it
+        let mut stable = r1.read().add_clock;
+        stable.glb(&r2.read().add_clock);
+        stable.glb(&bound);
+
+        let mut merged_then_forgotten = r1.clone();
+        merged_then_forgotten.merge(r2.clone());
+        merged_then_forgotten.forget(&stable);
+
+        r1.forget(&stable);
+        r2.forget(&stable);
+        let mut forgotten_then_merged = r1;
+        forgotten_then_merged.merge(r2);
+
+        assert_eq!(forgotten_then_merged, merged_then_forgotten);
+
+        // and nothing we forgot is resurrected by the merge
+        let resurrected = forgotten_then_merged
+            .read_with_clocks()
+            .val
+            .iter()
+            .flat_map(|(clock, _)| clock.iter())
+            .any(|dot| dot.counter <= stable.get(dot.actor));
+        TestResult::from_bool(!resurrected)
+    }
+
+    fn prop_compact_ops_is_replay_equivalent(
+        o1_ops: Vec<(u8, u8)>,
+        o2_ops: Vec<(u8, u8)>
+    ) -> TestResult {
+        if ops_are_not_compatible(&[&o1_ops, &o2_ops]) {
+            return TestResult::discard();
+        }
+        let mut log = build_test_reg(o1_ops).ops;
+        log.extend(build_test_reg(o2_ops).ops);
+
+        let mut replayed_log = MVReg::new();
+        for op in log.iter().cloned() {
+            replayed_log.apply(op);
+        }
+
+        let mut replayed_compacted = MVReg::new();
+        for op in MVReg::compact_ops(log) {
+            replayed_compacted.apply(op);
+        }
+
+        TestResult::from_bool(replayed_log == replayed_compacted)
+    }
+
     fn prop_op_idempotent(r_ops: Vec<(u8, u8)>) -> TestResult {
         let test = build_test_reg(r_ops);
         let mut r = test.reg;
@@ -263,6 +1197,109 @@ quickcheck! {
     }
 
 
+    fn prop_merge_delta_same_as_merge(
+        r1_ops: Vec<(u8, u8)>,
+        r2_ops: Vec<(u8, u8)>,
+        acked: usize
+    ) -> TestResult {
+        // disjoint actors, so the replicas never reuse each other's dots
+        let r1 = build_test_reg(r1_ops.into_iter().map(|(v, a)| (v, a % 128 * 2)).collect());
+        let r2_ops = r2_ops.into_iter().map(|(v, a)| (v, a % 128 * 2 + 1)).collect();
+        let mut peer = build_test_reg(r2_ops).reg;
+
+        // the peer has already been sent a prefix of r1's history
+        let mut r1_acked = MVReg::new();
+        for op in r1.ops.iter().take(acked % (r1.ops.len() + 1)) {
+            r1_acked.apply(op.clone());
+        }
+        peer.merge(r1_acked);
+
+        let mut merged = peer.clone();
+        merged.merge(r1.reg.clone());
+        peer.merge_delta(r1.reg.delta(&peer.read().add_clock));
+
+        assert_eq!(peer, merged);
+        TestResult::from_bool(true)
+    }
+
+    fn prop_serde_round_trip(r1_ops: Vec<(u8, u8)>, r2_ops: Vec<(u8, u8)>) -> TestResult {
+        if ops_are_not_compatible(&[&r1_ops, &r2_ops]) {
+            return TestResult::discard();
+        }
+        let r1 = build_test_reg(r1_ops);
+        let r2 = build_test_reg(r2_ops);
+        let mut reg = r1.reg;
+        reg.merge(r2.reg);
+        reg.apply_rm(&Dot::new(0, 1).into());
+
+        let bytes = bincode::serialize(&reg).unwrap();
+        let json = serde_json::to_string(&reg).unwrap();
+        let from_bincode: MVReg<u8, u8> = bincode::deserialize(&bytes).unwrap();
+        let from_json: MVReg<u8, u8> = serde_json::from_str(&json).unwrap();
+        assert_eq!(from_bincode.read_with_clocks(), reg.read_with_clocks());
+        assert_eq!(from_json.read_with_clocks(), reg.read_with_clocks());
+
+        let ops = r1.ops.into_iter().chain(r2.ops).chain(reg.ops_since(&VClock::new()));
+        for op in ops {
+            let bytes = bincode::serialize(&op).unwrap();
+            let json = serde_json::to_string(&op).unwrap();
+            assert_eq!(bincode::deserialize::<Op<u8, u8>>(&bytes).unwrap(), op);
+            assert_eq!(serde_json::from_str::<Op<u8, u8>>(&json).unwrap(), op);
+        }
+        TestResult::passed()
+    }
+
+    fn prop_clear_op_commutative(
+        o1_ops: Vec<(u8, u8)>,
+        o2_ops: Vec<(u8, u8)>
+    ) -> TestResult {
+        if ops_are_not_compatible(&[&o1_ops, &o2_ops]) {
+            return TestResult::discard();
+        }
+        let mut o1 = build_test_reg(o1_ops);
+        let o2 = build_test_reg(o2_ops);
+        let clear = o1.reg.clear(o1.reg.read().derive_rm_ctx());
+        o1.reg.apply(clear.clone());
+
+        let mut r1 = o1.reg;
+        let mut r2 = o2.reg;
+
+        for op in o2.ops.into_iter() {
+            r1.apply(op);
+        }
+
+        // deliver the clear before the puts it covers
+        r2.apply(clear);
+        for op in o1.ops.into_iter() {
+            r2.apply(op);
+        }
+
+        assert_eq!(r1, r2);
+        TestResult::from_bool(true)
+    }
+
+    fn prop_clear_merge_commutative(
+        r1_ops: Vec<(u8, u8)>,
+        r2_ops: Vec<(u8, u8)>
+    ) -> TestResult {
+        if ops_are_not_compatible(&[&r1_ops, &r2_ops]) {
+            return TestResult::discard();
+        }
+        let mut r1 = build_test_reg(r1_ops).reg;
+        let mut r2 = build_test_reg(r2_ops).reg;
+        let clear = r1.clear(r1.read().derive_rm_ctx());
+        r1.apply(clear.clone());
+
+        let r1_snapshot = r1.clone();
+        r1.merge(r2.clone());
+        r2.merge(r1_snapshot);
+
+        assert_eq!(r1, r2);
+        // nothing the clear covered resurfaces
+        let cleared = |c: &VClock<u8>| matches!(c.partial_cmp(clear.clock()), Some(Ordering::Less) | Some(Ordering::Equal));
+        TestResult::from_bool(!r1.read_with_clocks().val.iter().any(|(c, _)| cleared(c)))
+    }
+
     fn prop_op_associative(
         o1_ops: Vec<(u8, u8)>,
         o2_ops: Vec<(u8, u8)>,
@@ -302,3 +1339,378 @@ quickcheck! {
         TestResult::from_bool(true)
     }
 }
+
+#[test]
+fn test_merge_resolving_collapses_conflict() {
+    let mut reg_a: MVReg<u8, u8> = MVReg::new();
+    let mut reg_b: MVReg<u8, u8> = MVReg::new();
+    reg_a.apply(reg_a.write(3, reg_a.read_ctx().derive_add_ctx(1)));
+    reg_b.apply(reg_b.write(7, reg_b.read_ctx().derive_add_ctx(2)));
+
+    reg_a.merge_resolving(&reg_b, |a, b| *a.max(b));
+    assert_eq!(reg_a.read().val, vec![7]);
+
+    // the resolved value dominates both writes, so it survives a plain merge
+    reg_b.merge(reg_a.clone());
+    assert_eq!(reg_b.read().val, vec![7]);
+    assert_eq!(reg_a, reg_b);
+}
+
+#[test]
+fn test_merge_resolving_converges_with_non_commutative_resolver() {
+    let mut reg_a: MVReg<u8, u8> = MVReg::new();
+    let mut reg_b: MVReg<u8, u8> = MVReg::new();
+    reg_a.apply(reg_a.write(3, reg_a.read_ctx().derive_add_ctx(1)));
+    reg_b.apply(reg_b.write(7, reg_b.read_ctx().derive_add_ctx(2)));
+
+    // keeping the left value depends on the fold order, which must not depend on
+    // which replica merges into which
+    let mut a_into_b = reg_b.clone();
+    a_into_b.merge_resolving(&reg_a, |a, _| *a);
+    let mut b_into_a = reg_a.clone();
+    b_into_a.merge_resolving(&reg_b, |a, _| *a);
+
+    assert_eq!(a_into_b.read().val, b_into_a.read().val);
+    assert_eq!(a_into_b, b_into_a);
+}
+
+#[test]
+fn test_ops_since_catches_up_lagging_peer() {
+    let mut reg: MVReg<u8, u8> = MVReg::new();
+    reg.apply(reg.write(1, reg.read_ctx().derive_add_ctx(1)));
+    let mut peer = reg.clone();
+    let peer_clock = peer.read_ctx().add_clock;
+
+    // one value concurrent with what the peer has seen, and one the peer already has
+    let mut other: MVReg<u8, u8> = MVReg::new();
+    other.apply(other.write(2, other.read_ctx().derive_add_ctx(2)));
+    reg.merge(other);
+    reg.apply(reg.write(3, reg.read_ctx().derive_add_ctx(1)));
+    let mut concurrent: MVReg<u8, u8> = MVReg::new();
+    concurrent.apply(concurrent.write(4, concurrent.read_ctx().derive_add_ctx(3)));
+    reg.merge(concurrent);
+
+    assert!(reg.ops_since(&reg.read_ctx().add_clock).is_empty());
+
+    let ops = reg.ops_since(&peer_clock);
+    assert_eq!(ops.len(), 2);
+    for op in ops {
+        peer.apply(op);
+    }
+    assert_eq!(peer, reg);
+}
+
+#[test]
+fn test_into_iter_yields_values_with_clocks() {
+    let mut reg: MVReg<u8, u8> = MVReg::new();
+    let mut other: MVReg<u8, u8> = MVReg::new();
+    reg.apply(reg.write(1, reg.read_ctx().derive_add_ctx(1)));
+    other.apply(other.write(2, other.read_ctx().derive_add_ctx(2)));
+    reg.merge(other);
+
+    let expected = reg.read_with_clocks().val;
+    let vals: Vec<(VClock<u8>, u8)> = reg.into_iter().collect();
+    assert_eq!(vals, expected);
+    assert_eq!(
+        vals,
+        vec![
+            (VClock::from(Dot::new(1, 1)), 1),
+            (VClock::from(Dot::new(2, 1)), 2)
+        ]
+    );
+}
+
+/// A Lamport timestamp context, it totally orders puts so the register is last-writer-wins
+#[derive(Debug, Clone, Copy, Default, PartialEq, PartialOrd)]
+struct Lamport(u64);
+
+impl CausalContext for Lamport {
+    fn join(&mut self, other: &Self) {
+        self.0 = self.0.max(other.0);
+    }
+
+    fn subtract(&mut self, other: &Self) {
+        if self.0 <= other.0 {
+            self.0 = 0;
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+}
+
+#[test]
+fn test_alternative_causal_context_converges() {
+    let mut replicas: Vec<MVReg<&str, u8, Lamport>> = (0..3).map(|_| MVReg::default()).collect();
+    replicas[0].put(Lamport(1), "a");
+    replicas[1].put(Lamport(3), "b");
+    replicas[2].put(Lamport(2), "c");
+    replicas[2].put(Lamport(1), "stale");
+
+    let mut forward = MVReg::default();
+    let mut backward = MVReg::default();
+    for reg in replicas.iter() {
+        forward.merge(reg.clone());
+    }
+    for reg in replicas.into_iter().rev() {
+        backward.merge(reg);
+    }
+
+    assert_eq!(forward, backward);
+    assert_eq!(
+        forward.into_iter().collect::<Vec<_>>(),
+        vec![(Lamport(3), "b")]
+    );
+}
+
+#[test]
+fn test_resolving_conflict_appends_one_audit_entry() {
+    let mut reg: MVReg<u8, u8> = MVReg::new();
+    reg.enable_resolution_log();
+    let mut peer: MVReg<u8, u8> = MVReg::new();
+    reg.apply(reg.write(3, reg.read_ctx().derive_add_ctx(1)));
+    peer.apply(peer.write(7, peer.read_ctx().derive_add_ctx(2)));
+    reg.merge(peer);
+    assert_eq!(reg.resolution_log(), &[]);
+
+    // writing with a context covering both concurrent values resolves the conflict
+    let op = reg.write(5, reg.read_ctx().derive_add_ctx(1));
+    let clock = op.clock().clone();
+    reg.apply(op);
+
+    assert_eq!(
+        reg.resolution_log(),
+        &[Resolution {
+            clock,
+            chosen: 5,
+            discarded: vec![3, 7],
+        }]
+    );
+}
+
+#[test]
+fn test_ops_apply_to_registers_of_values_that_cant_be_cloned() {
+    #[derive(Debug, PartialEq)]
+    struct Handle(u8);
+
+    let mut reg: MVReg<Handle, u8> = MVReg::new();
+    let ctx = reg.read_ctx().derive_add_ctx(1);
+    reg.apply(reg.write(Handle(3), ctx));
+    let ctx = reg.read_ctx().derive_add_ctx(1);
+    reg.apply(reg.write(Handle(5), ctx));
+
+    assert_eq!(reg.read_iter().val.collect::<Vec<_>>(), vec![&Handle(5)]);
+}
+
+#[test]
+fn test_resolution_log_is_disabled_by_default() {
+    let mut reg_a: MVReg<u8, u8> = MVReg::new();
+    let mut reg_b: MVReg<u8, u8> = MVReg::new();
+    reg_a.apply(reg_a.write(3, reg_a.read_ctx().derive_add_ctx(1)));
+    reg_b.apply(reg_b.write(7, reg_b.read_ctx().derive_add_ctx(2)));
+
+    reg_a.merge_resolving(&reg_b, |a, b| *a.max(b));
+    assert_eq!(reg_a.resolution_log(), &[]);
+
+    reg_b.enable_resolution_log();
+    reg_b.apply(reg_b.write(1, reg_b.read_ctx().derive_add_ctx(2)));
+    let mut reg_c: MVReg<u8, u8> = MVReg::new();
+    reg_c.apply(reg_c.write(4, reg_c.read_ctx().derive_add_ctx(3)));
+    reg_b.merge_resolving(&reg_c, |a, b| *a.max(b));
+    assert_eq!(reg_b.resolution_log().len(), 1);
+    assert_eq!(reg_b.resolution_log()[0].chosen, 4);
+    assert_eq!(reg_b.resolution_log()[0].discarded, vec![1, 4]);
+}
+
+#[test]
+fn test_apply_returning_yields_previous_values() {
+    let mut reg: MVReg<u8, u8> = MVReg::new();
+    let mut peer: MVReg<u8, u8> = MVReg::new();
+    reg.apply(reg.write(3, reg.read_ctx().derive_add_ctx(1)));
+    peer.apply(peer.write(7, peer.read_ctx().derive_add_ctx(2)));
+    reg.merge(peer);
+
+    let before = reg.read().val;
+    let op = reg.write(5, reg.read_ctx().derive_add_ctx(1));
+    assert_eq!(reg.apply_returning(&op), before);
+    assert_eq!(reg.read().val, vec![5]);
+
+    assert_eq!(reg.apply_returning(&op), vec![5]);
+}
+
+#[test]
+fn test_clear_keeps_concurrent_put() {
+    let mut reg: MVReg<u8, u8> = MVReg::new();
+    let mut peer: MVReg<u8, u8> = MVReg::new();
+    reg.apply(reg.write(1, reg.read_ctx().derive_add_ctx(1)));
+
+    let clear = reg.clear(reg.read().derive_rm_ctx());
+    let concurrent = peer.write(2, peer.read_ctx().derive_add_ctx(2));
+    reg.apply(clear.clone());
+    assert_eq!(reg.read().val, Vec::<u8>::new());
+
+    reg.apply(concurrent.clone());
+    peer.apply(concurrent);
+    peer.apply(clear);
+    assert_eq!(reg.read().val, vec![2]);
+    assert_eq!(reg, peer);
+}
+
+#[test]
+fn test_clear_from_stale_read_keeps_later_write() {
+    let mut reg: MVReg<u8, u8> = MVReg::new();
+    reg.apply(reg.write(1, reg.read_ctx().derive_add_ctx(1)));
+
+    // the clear is derived from a read that only observed the first value
+    let stale = reg.read();
+    reg.apply(reg.write(2, reg.read_ctx().derive_add_ctx(2)));
+    reg.apply(reg.clear(stale.derive_rm_ctx()));
+    assert_eq!(reg.read().val, vec![2]);
+
+    // a fresh read covers the value, so clearing with it empties the register
+    reg.apply(reg.clear(reg.read().derive_rm_ctx()));
+    assert_eq!(reg.read().val, Vec::<u8>::new());
+}
+
+#[test]
+fn test_write_after_forget_dominates_partially_forgotten_value() {
+    let mut reg: MVReg<u8, u8> = MVReg::new();
+    reg.apply(reg.write(1, reg.read().derive_add_ctx(2)));
+    reg.apply(reg.write(2, reg.read().derive_add_ctx(1)));
+    let mut peer = reg.clone();
+
+    // forgetting part of the value's history trims its clock
+    let stable = VClock::from(Dot::new(1, 1));
+    reg.forget(&stable);
+    assert_eq!(reg.read_with_clocks().val, vec![(Dot::new(2, 1).into(), 2)]);
+
+    // a write derived from a read after the forget must not reuse the forgotten dot
+    let op = reg.write(3, reg.read().derive_add_ctx(1));
+    assert_eq!(op.clock().get(&1), 2);
+    reg.apply(op.clone());
+    assert_eq!(reg.read().val, vec![3]);
+
+    // so it also replaces the value on a replica that forgets later
+    peer.apply(op);
+    peer.forget(&stable);
+    assert_eq!(peer, reg);
+    assert_eq!(peer.read().val, vec![3]);
+}
+
+#[test]
+fn test_write_after_clear_is_kept() {
+    let mut reg: MVReg<u8, u8> = MVReg::new();
+    reg.apply(reg.write(1, reg.read().derive_add_ctx(1)));
+    reg.apply(reg.clear(reg.read().derive_rm_ctx()));
+
+    reg.apply(reg.write(2, reg.read().derive_add_ctx(1)));
+    assert_eq!(reg.read().val, vec![2]);
+}
+
+#[test]
+fn test_cleared_value_does_not_resurface_on_merge() {
+    let mut reg: MVReg<u8, u8> = MVReg::new();
+    reg.apply(reg.write(1, reg.read_ctx().derive_add_ctx(1)));
+    let stale = reg.clone();
+
+    reg.apply(reg.clear(reg.read().derive_rm_ctx()));
+    reg.merge(stale.clone());
+    assert_eq!(reg.read().val, Vec::<u8>::new());
+
+    let mut stale = stale;
+    let ops = reg.ops_since(&stale.read().add_clock);
+    assert_eq!(ops.len(), 1);
+    for op in ops {
+        stale.apply(op);
+    }
+    assert_eq!(stale.read().val, Vec::<u8>::new());
+}
+
+#[test]
+fn test_read_lww_picks_highest_actor_on_every_replica() {
+    let mut r1: MVReg<&str, u8> = MVReg::new();
+    let mut r2: MVReg<&str, u8> = MVReg::new();
+    assert_eq!(r1.read_lww().val, None);
+
+    let op1 = r1.write("from 7", r1.read().derive_add_ctx(7));
+    let op2 = r2.write("from 4", r2.read().derive_add_ctx(4));
+    r1.apply(op1.clone());
+    r1.apply(op2.clone());
+    r2.apply(op2);
+    r2.apply(op1);
+
+    // both values are kept, each replica independently reads the same winner
+    assert_eq!(r1.read().val.len(), 2);
+    assert_eq!(r1.read_lww().val, Some("from 7"));
+    assert_eq!(r2.read_lww().val, Some("from 7"));
+    assert_eq!(r1.read_lww().add_clock, r1.read().add_clock);
+
+    // a write derived from the lww read replaces both values
+    r2.apply(r2.write("resolved", r2.read_lww().derive_add_ctx(4)));
+    assert_eq!(r2.read().val, vec!["resolved"]);
+}
+
+#[test]
+fn test_read_resolved_folds_concurrent_values() {
+    let mut reg: MVReg<u8, u8> = MVReg::new();
+    assert_eq!(reg.read_latest().val, None);
+    assert_eq!(reg.read_resolved(|a, b| a + b).val, None);
+
+    let mut peer: MVReg<u8, u8> = MVReg::new();
+    reg.apply(reg.write(3, reg.read_ctx().derive_add_ctx(1)));
+    peer.apply(peer.write(7, peer.read_ctx().derive_add_ctx(2)));
+    reg.merge(peer);
+
+    assert_eq!(reg.read_resolved(|a, b| a + b).val, Some(10));
+    let latest = reg.read_latest();
+    assert_eq!(latest.val, Some(7));
+    assert_eq!(latest.add_clock, reg.read().add_clock);
+
+    // a write derived from the resolved read replaces both values
+    let op = reg.write(7, latest.derive_add_ctx(1));
+    reg.apply(op);
+    assert_eq!(reg.read().val, vec![7]);
+}
+
+#[test]
+fn test_delta_holds_unseen_values() {
+    let mut reg: MVReg<u8, u8> = MVReg::new();
+    reg.apply(reg.write(1, reg.read_ctx().derive_add_ctx(1)));
+    let mut peer = reg.clone();
+    reg.apply(reg.write(2, reg.read_ctx().derive_add_ctx(1)));
+
+    let delta = reg.delta(&peer.read().add_clock);
+    assert_eq!(delta.read().val, vec![2]);
+    assert!(reg.delta(&reg.read().add_clock).is_bottom());
+
+    peer.merge_delta(delta);
+    assert_eq!(peer, reg);
+}
+
+#[test]
+fn test_conflicting_puts_converge_in_either_order() {
+    let clock = VClock::from(Dot::new(1, 1));
+    let put_a = Op::Put {
+        clock: clock.clone(),
+        val: 1,
+    };
+    let put_b = Op::Put { clock, val: 2 };
+
+    let mut r1: MVReg<u8, u8> = MVReg::new();
+    r1.apply(put_a.clone());
+    r1.apply(put_b.clone());
+
+    let mut r2: MVReg<u8, u8> = MVReg::new();
+    r2.apply(put_b.clone());
+    r2.apply(put_a.clone());
+
+    assert_eq!(r1, r2);
+    assert_eq!(r1.read().val, vec![1, 2]);
+
+    // redelivering either op changes nothing
+    r2.apply(put_a);
+    r2.apply(put_b);
+    assert_eq!(r1, r2);
+    assert_eq!(r2.read().val, vec![2, 1]);
+}
@@ -0,0 +1,99 @@
+use crdts::mvreg::Op;
+use crdts::{CmRDT, MVReg};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+const REPLICAS: usize = 4;
+
+/// An op in flight to a replica
+struct Packet {
+    to: usize,
+    op: Op<u8, usize>,
+}
+
+/// A lossy network: packets may be dropped, duplicated and delivered in any order.
+///
+/// Dropped packets aren't lost for good, they are retransmitted once the network
+/// is flushed, so that every op is eventually delivered.
+struct Network {
+    rng: StdRng,
+    in_flight: Vec<Packet>,
+    dropped: Vec<Packet>,
+}
+
+impl Network {
+    fn new(seed: u64) -> Self {
+        Self {
+            rng: StdRng::seed_from_u64(seed),
+            in_flight: Vec::new(),
+            dropped: Vec::new(),
+        }
+    }
+
+    fn broadcast(&mut self, from: usize, op: &Op<u8, usize>) {
+        for to in (0..REPLICAS).filter(|to| *to != from) {
+            let packet = Packet { to, op: op.clone() };
+            if self.rng.gen_ratio(1, 4) {
+                self.dropped.push(packet);
+            } else {
+                if self.rng.gen_ratio(1, 4) {
+                    self.in_flight.push(Packet { to, op: op.clone() });
+                }
+                self.in_flight.push(packet);
+            }
+        }
+    }
+
+    /// Deliver a random in flight packet, if there is one
+    fn deliver_one(&mut self, replicas: &mut [MVReg<u8, usize>]) {
+        if !self.in_flight.is_empty() {
+            let ix = self.rng.gen_range(0, self.in_flight.len());
+            let Packet { to, op } = self.in_flight.swap_remove(ix);
+            replicas[to].apply(op);
+        }
+    }
+
+    /// Retransmit the dropped packets and deliver everything
+    fn flush(&mut self, replicas: &mut [MVReg<u8, usize>]) {
+        self.in_flight.append(&mut self.dropped);
+        while !self.in_flight.is_empty() {
+            self.deliver_one(replicas);
+        }
+    }
+}
+
+fn simulate(seed: u64) {
+    let mut net = Network::new(seed);
+    let mut replicas: Vec<MVReg<u8, usize>> = (0..REPLICAS).map(|_| MVReg::new()).collect();
+    let mut all_ops = Vec::new();
+
+    for _ in 0..200 {
+        if net.rng.gen_ratio(1, 3) {
+            let actor = net.rng.gen_range(0, REPLICAS);
+            let val = net.rng.gen();
+            let reg = &mut replicas[actor];
+            let op = reg.write(val, reg.read_ctx().derive_add_ctx(actor));
+            reg.apply(op.clone());
+            net.broadcast(actor, &op);
+            all_ops.push(op);
+        } else {
+            net.deliver_one(&mut replicas);
+        }
+    }
+    net.flush(&mut replicas);
+
+    let mut reference = MVReg::new();
+    for op in all_ops {
+        reference.apply(op);
+    }
+    for reg in replicas.iter() {
+        assert_eq!(reg, &reference, "seed {} did not converge", seed);
+    }
+}
+
+#[test]
+fn test_converges_over_lossy_network() {
+    for seed in 0..50 {
+        simulate(seed);
+    }
+}
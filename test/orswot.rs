@@ -68,7 +68,6 @@ fn weird_highlight_1() {
     assert!(a.read().val.is_empty());
 }
 
-///
 #[test]
 fn adds_dont_destroy_causality() {
     let mut a = Orswot::new();
@@ -116,7 +115,7 @@ fn merge_clocks_of_identical_entries() {
     let mut final_clock = VClock::new();
     final_clock.apply(final_clock.inc("A"));
     final_clock.apply(final_clock.inc("B"));
-    assert_eq!(a.contains(&1).val, true);
+    assert!(a.contains(&1).val);
     assert_eq!(a.contains(&1).rm_clock, final_clock);
 }
 
@@ -5,6 +5,8 @@ extern crate crdts;
 
 mod lseq;
 mod map;
+mod merge_order;
 mod mvreg;
+mod mvreg_net;
 mod orswot;
 mod vclock;
@@ -1,12 +1,23 @@
-use crdts::*;
+use crdts::{
+    dot::OrdDot,
+    vclock::{CausalOrder, DecodeError},
+    *,
+};
 
+use std::cell::Cell;
 use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::hash::{Hash, Hasher};
 
 quickcheck! {
     fn prop_into_iter_produces_same_vclock(clock: VClock<u8>) -> bool {
         clock == clock.clone().into_iter().collect()
     }
 
+    fn prop_dots_match_into_iter(clock: VClock<u8>) -> bool {
+        clock.dots().eq(clock.clone().into_iter())
+    }
+
     fn prop_dots_are_commutative_in_from_iter(dots: Vec<Dot<u8>>) -> bool {
         // TODO: is there a better way to check comutativity of dots?
         let reverse: VClock<u8> = dots.clone()
@@ -50,6 +61,96 @@ quickcheck! {
         a_glb == b_glb
     }
 
+    fn prop_meet_matches_glb(a: VClock<u8>, b: VClock<u8>) -> bool {
+        let mut a_glb = a.clone();
+        a_glb.glb(&b);
+        a.meet(&b) == a_glb && a.meet(&b) <= a && a.meet(&b) <= b
+    }
+
+    fn prop_merge_absorbs_meet(a: VClock<u8>, b: VClock<u8>) -> bool {
+        // a ∨ (a ∧ b) = a
+        let mut absorbed = a.clone();
+        absorbed.merge(a.meet(&b));
+        absorbed == a
+    }
+
+    fn prop_meet_absorbs_merge(a: VClock<u8>, b: VClock<u8>) -> bool {
+        // a ∧ (a ∨ b) = a
+        let mut merged = a.clone();
+        merged.merge(b);
+        a.meet(&merged) == a
+    }
+
+    fn prop_clone_without_and_meet_rebuild_clock(a: VClock<u8>, b: VClock<u8>) -> bool {
+        let mut rebuilt = a.clone_without(&b);
+        rebuilt.merge(a.meet(&b));
+        rebuilt == a
+    }
+
+    fn prop_merge_idempotent(clock: VClock<u8>) -> bool {
+        let mut merged = clock.clone();
+        merged.merge(clock.clone());
+        merged == clock
+    }
+
+    fn prop_merge_commutative(a: VClock<u8>, b: VClock<u8>) -> bool {
+        let mut ab = a.clone();
+        ab.merge(b.clone());
+
+        let mut ba = b;
+        ba.merge(a);
+
+        ab == ba
+    }
+
+    fn prop_merge_associative(a: VClock<u8>, b: VClock<u8>, c: VClock<u8>) -> bool {
+        // (a ^ b) ^ c
+        let mut ab_c = a.clone();
+        ab_c.merge(b.clone());
+        ab_c.merge(c.clone());
+
+        // a ^ (b ^ c)
+        let mut bc = b;
+        bc.merge(c);
+        let mut a_bc = a;
+        a_bc.merge(bc);
+
+        ab_c == a_bc
+    }
+
+    fn prop_merge_is_least_upper_bound(a: VClock<u8>, b: VClock<u8>) -> bool {
+        let mut merged = a.clone();
+        merged.merge(b.clone());
+
+        // the merge dominates both inputs
+        let dominates_inputs = a <= merged && b <= merged;
+
+        // and is equal to one of the inputs exactly when it dominates the other
+        let agrees_with_order = (a >= b) == (merged == a) && (b >= a) == (merged == b);
+
+        dominates_inputs && agrees_with_order
+    }
+
+    fn prop_serialization_is_deterministic(dots: Vec<(u8, u64)>) -> bool {
+        // the same dots applied in opposite orders build equal clocks
+        let forward: VClock<u8> = dots.iter().map(|(a, c)| Dot::new(*a, *c)).collect();
+        let backward: VClock<u8> = dots.iter().rev().map(|(a, c)| Dot::new(*a, *c)).collect();
+        assert_eq!(forward, backward);
+
+        bincode::serialize(&forward).unwrap() == bincode::serialize(&backward).unwrap()
+            && serde_json::to_string(&forward).unwrap() == serde_json::to_string(&backward).unwrap()
+    }
+
+    fn prop_varint_round_trip(clock: VClock<u8>) -> bool {
+        VClock::decode_varint(&clock.encode_varint()) == Ok(clock)
+    }
+
+    fn prop_merge_with_bottom_is_nop(clock: VClock<u8>) -> bool {
+        let mut merged = clock.clone();
+        merged.merge(VClock::bottom());
+        merged == clock && VClock::<u8>::bottom().is_bottom()
+    }
+
     fn prop_forget_with_empty_is_nop(clock: VClock<u8>) -> bool {
         let mut subbed  = clock.clone();
         subbed.forget(&VClock::new());
@@ -67,19 +168,64 @@ quickcheck! {
         a.forget(&b);
 
         if a.is_empty() {
-            match a.partial_cmp(&b) {
-                Some(Ordering::Less) | Some(Ordering::Equal) => true,
-                _ => false
-            }
+            matches!(a.partial_cmp(&b), Some(Ordering::Less) | Some(Ordering::Equal))
         } else {
-            match a.partial_cmp(&b) {
-                None | Some(Ordering::Greater) => true,
-                _ => false
-            }
+            matches!(a.partial_cmp(&b), None | Some(Ordering::Greater))
         }
     }
 }
 
+#[test]
+fn test_meet_disjoint_actors_is_empty() {
+    let a: VClock<u8> = vec![Dot::new(1, 4), Dot::new(2, 3)].into_iter().collect();
+    let b: VClock<u8> = vec![Dot::new(3, 5), Dot::new(4, 1)].into_iter().collect();
+
+    assert!(a.meet(&b).is_empty());
+    assert!(b.meet(&a).is_empty());
+}
+
+#[test]
+fn test_meet_of_nested_clocks_is_the_smaller() {
+    let small: VClock<u8> = vec![Dot::new(1, 2), Dot::new(2, 3)].into_iter().collect();
+    let big: VClock<u8> = vec![Dot::new(1, 5), Dot::new(2, 3), Dot::new(3, 1)]
+        .into_iter()
+        .collect();
+    assert!(small < big);
+
+    assert_eq!(small.meet(&big), small);
+    assert_eq!(big.meet(&small), small);
+}
+
+#[test]
+fn test_meet_takes_min_of_shared_actors() {
+    let a: VClock<u8> = vec![Dot::new(1, 4), Dot::new(2, 1), Dot::new(5, 9)]
+        .into_iter()
+        .collect();
+    let b: VClock<u8> = vec![Dot::new(1, 2), Dot::new(2, 7), Dot::new(6, 3)]
+        .into_iter()
+        .collect();
+    let expected: VClock<u8> = vec![Dot::new(1, 2), Dot::new(2, 1)].into_iter().collect();
+
+    assert_eq!(a.meet(&b), expected);
+    assert_eq!(a.meet(&a), a);
+}
+
+#[test]
+fn test_clone_without_leaves_clock_untouched() {
+    let a: VClock<u8> = vec![Dot::new(1, 4), Dot::new(2, 3), Dot::new(5, 9)]
+        .into_iter()
+        .collect();
+    let b: VClock<u8> = vec![Dot::new(1, 5), Dot::new(2, 3), Dot::new(5, 8)]
+        .into_iter()
+        .collect();
+    let snapshot = a.clone();
+
+    assert_eq!(a.clone_without(&b), VClock::from(Dot::new(5, 9)));
+    assert_eq!(a.clone_without(&VClock::new()), a);
+    assert!(a.clone_without(&a).is_empty());
+    assert_eq!(a, snapshot);
+}
+
 #[test]
 fn test_forget() {
     let mut a: VClock<u8> = vec![Dot::new(1, 4), Dot::new(2, 3), Dot::new(5, 9)]
@@ -94,6 +240,271 @@ fn test_forget() {
     assert_eq!(a, expected);
 }
 
+#[test]
+fn test_overlay() {
+    let mut a: VClock<&str> = vec![Dot::new("a", 1), Dot::new("b", 2)]
+        .into_iter()
+        .collect();
+    let b: VClock<&str> = vec![Dot::new("a", 5), Dot::new("b", 7), Dot::new("c", 1)]
+        .into_iter()
+        .collect();
+
+    a.overlay(&b, &vec!["a"].into_iter().collect());
+
+    let expected: VClock<&str> = vec![Dot::new("a", 5), Dot::new("b", 2)]
+        .into_iter()
+        .collect();
+    assert_eq!(a, expected);
+
+    // overlaying never moves an actor backwards
+    let behind: VClock<&str> = Dot::new("a", 2).into();
+    a.overlay(&behind, &vec!["a", "b"].into_iter().collect());
+    assert_eq!(a, expected);
+}
+
+#[test]
+fn test_actors_at_least() {
+    let clock: VClock<&str> = vec![Dot::new("a", 3), Dot::new("b", 1), Dot::new("c", 5)]
+        .into_iter()
+        .collect();
+
+    assert_eq!(clock.actors_at_least(3), 2);
+    assert_eq!(clock.actors_at_least(0), 3);
+    assert_eq!(clock.actors_at_least(6), 0);
+}
+
+#[test]
+fn test_forget_leaves_unacked_entries() {
+    let mut unacked: VClock<&str> = vec![Dot::new("a", 3), Dot::new("b", 2), Dot::new("c", 5)]
+        .into_iter()
+        .collect();
+    let peer_ack: VClock<&str> = vec![Dot::new("a", 3), Dot::new("b", 4), Dot::new("c", 4)]
+        .into_iter()
+        .collect();
+
+    unacked.forget(&peer_ack);
+
+    assert_eq!(unacked, VClock::from(Dot::new("c", 5)));
+}
+
+#[test]
+fn test_union_actor_count() {
+    let a: VClock<u8> = vec![Dot::new(1, 1), Dot::new(2, 3), Dot::new(3, 1)]
+        .into_iter()
+        .collect();
+    let b: VClock<u8> = vec![Dot::new(2, 1), Dot::new(3, 4), Dot::new(4, 2)]
+        .into_iter()
+        .collect();
+
+    assert_eq!(a.union_actor_count(&b), 4);
+    assert_eq!(b.union_actor_count(&a), 4);
+    assert_eq!(a.union_actor_count(&VClock::new()), 3);
+
+    let mut merged = a.clone();
+    merged.merge(b.clone());
+    assert_eq!(a.union_actor_count(&b), merged.iter().count());
+}
+
+#[test]
+fn test_split_by() {
+    let clock: VClock<&str> = vec![Dot::new("a", 5), Dot::new("b", 2)]
+        .into_iter()
+        .collect();
+
+    let (acked, pending) = clock.split_by(&VClock::from(Dot::new("a", 3)));
+    assert_eq!(acked, VClock::from(Dot::new("a", 3)));
+    assert_eq!(pending, clock);
+
+    let ack: VClock<&str> = vec![Dot::new("a", 5), Dot::new("b", 1)]
+        .into_iter()
+        .collect();
+    let (acked, pending) = clock.split_by(&ack);
+    assert_eq!(acked, ack);
+    assert_eq!(pending, VClock::from(Dot::new("b", 2)));
+
+    let (acked, pending) = clock.split_by(&clock);
+    assert_eq!(acked, clock);
+    assert!(pending.is_empty());
+}
+
+#[test]
+fn test_get_dot_and_inc_of_unknown_actor() {
+    let clock: VClock<u8> = VClock::from(Dot::new(1, 4));
+
+    assert_eq!(clock.get(&2), 0);
+    assert_eq!(clock.dot(2), Dot::new(2, 0));
+    assert_eq!(clock.inc(2), Dot::new(2, 1));
+}
+
+#[test]
+fn test_inc_does_not_mutate_clock() {
+    let mut clock: VClock<u8> = VClock::new();
+
+    // the first increment of an actor is its first dot
+    let first = clock.inc(1);
+    assert_eq!(first, Dot::new(1, 1));
+    assert_eq!(clock.inc(1), first);
+    assert_eq!(clock.get(&1), 0);
+
+    // each applied increment moves the next one along
+    clock.apply(first);
+    assert_eq!(clock.dot(1), Dot::new(1, 1));
+    for counter in 2..5 {
+        let dot = clock.inc(1);
+        assert_eq!(dot, Dot::new(1, counter));
+        clock.apply(dot);
+    }
+    assert_eq!(clock.get(&1), 4);
+}
+
+#[test]
+fn test_clear() {
+    let mut clock: VClock<u8> = vec![Dot::new(1, 2), Dot::new(3, 4)].into_iter().collect();
+    clock.clear();
+
+    assert!(clock.is_empty());
+    assert_eq!(clock, VClock::new());
+}
+
+#[test]
+fn test_ready_for() {
+    let clock: VClock<&str> = vec![Dot::new("a", 2), Dot::new("b", 1)]
+        .into_iter()
+        .collect();
+    let dep: VClock<&str> = vec![Dot::new("a", 1), Dot::new("b", 1)]
+        .into_iter()
+        .collect();
+
+    // all dependencies seen and the op is next from "a"
+    assert!(clock.ready_for(&dep, &Dot::new("a", 3)));
+
+    // the op depends on an op from "c" we haven't seen yet
+    let missing_dep: VClock<&str> = vec![Dot::new("b", 1), Dot::new("c", 1)]
+        .into_iter()
+        .collect();
+    assert!(!clock.ready_for(&missing_dep, &Dot::new("a", 3)));
+
+    // there's a gap: we haven't seen "a"'s third op
+    assert!(!clock.ready_for(&dep, &Dot::new("a", 4)));
+
+    // already delivered
+    assert!(!clock.ready_for(&dep, &Dot::new("a", 2)));
+
+    // a peer can't make us overflow an exhausted counter
+    let exhausted: VClock<&str> = Dot::new("a", u64::MAX).into();
+    assert!(!exhausted.ready_for(&VClock::new(), &Dot::new("a", 0)));
+    assert!(!exhausted.ready_for(&VClock::new(), &Dot::new("a", u64::MAX)));
+}
+
+thread_local! {
+    static ACTOR_CLONES: Cell<usize> = const { Cell::new(0) };
+    static ACTOR_COMPARISONS: Cell<usize> = const { Cell::new(0) };
+}
+
+/// An actor that counts how many times it has been cloned
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+struct CountedActor(u8);
+
+impl Clone for CountedActor {
+    fn clone(&self) -> Self {
+        ACTOR_CLONES.with(|clones| clones.set(clones.get() + 1));
+        CountedActor(self.0)
+    }
+}
+
+/// An actor that counts how many times it has been compared for equality
+#[derive(Debug, Clone, Eq, PartialOrd, Ord)]
+struct ComparedActor(u8);
+
+impl Hash for ComparedActor {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.hash(state);
+    }
+}
+
+impl PartialEq for ComparedActor {
+    fn eq(&self, other: &Self) -> bool {
+        ACTOR_COMPARISONS.with(|comparisons| comparisons.set(comparisons.get() + 1));
+        self.0 == other.0
+    }
+}
+
+#[test]
+fn test_eq_short_circuits_on_size() {
+    let small: VClock<_> = (0..3).map(|i| Dot::new(ComparedActor(i), 1)).collect();
+    let large: VClock<_> = (0..100).map(|i| Dot::new(ComparedActor(i), 1)).collect();
+
+    ACTOR_COMPARISONS.with(|comparisons| comparisons.set(0));
+    assert_ne!(small, large);
+    assert_eq!(ACTOR_COMPARISONS.with(|comparisons| comparisons.get()), 0);
+
+    // clocks of the same size still compare entries
+    assert_eq!(small, small.clone());
+    assert_eq!(ACTOR_COMPARISONS.with(|comparisons| comparisons.get()), 3);
+}
+
+#[test]
+fn test_merge_ref() {
+    let mut a: VClock<u8> = vec![Dot::new(1, 1), Dot::new(4, 4)].into_iter().collect();
+    let b: BTreeMap<u8, u64> = vec![(3, 3), (4, 3), (5, 0)].into_iter().collect();
+
+    a.merge_ref(b.iter());
+
+    let expected: VClock<u8> = vec![Dot::new(1, 1), Dot::new(3, 3), Dot::new(4, 4)]
+        .into_iter()
+        .collect();
+    assert_eq!(a, expected);
+}
+
+#[test]
+fn test_merge_ref_only_clones_new_actors() {
+    let known: Vec<_> = (0..10).map(|i| Dot::new(CountedActor(i), 1)).collect();
+    let mut local: VClock<CountedActor> = known.into_iter().collect();
+    let remote: BTreeMap<CountedActor, u64> = (0..11).map(|i| (CountedActor(i), 2)).collect();
+
+    ACTOR_CLONES.with(|clones| clones.set(0));
+    let mut merged = local.clone();
+    merged.merge(
+        remote
+            .iter()
+            .map(|(a, c)| Dot::new(a.clone(), *c))
+            .collect(),
+    );
+    let clones_with_merge = ACTOR_CLONES.with(|clones| clones.replace(0));
+
+    local.merge_ref(remote.iter());
+    let clones_with_merge_ref = ACTOR_CLONES.with(|clones| clones.get());
+
+    assert_eq!(local, merged);
+    assert_eq!(clones_with_merge_ref, 1);
+    assert!(clones_with_merge_ref < clones_with_merge);
+}
+
+/// An actor whose Ord only looks at the first field while Eq looks at both
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct CollidingActor(u8, u8);
+
+impl PartialOrd for CollidingActor {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for CollidingActor {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.cmp(&other.0)
+    }
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "distinct actors collided")]
+fn test_merge_detects_colliding_actors() {
+    let mut a = VClock::from(Dot::new(CollidingActor(1, 1), 1));
+    let b = VClock::from(Dot::new(CollidingActor(1, 2), 2));
+    a.merge(b);
+}
+
 #[test]
 fn test_merge() {
     let mut a: VClock<u8> = vec![Dot::new(1, 1), Dot::new(4, 4)].into_iter().collect();
@@ -108,6 +519,17 @@ fn test_merge() {
     assert_eq!(a, expected);
 }
 
+#[test]
+fn test_merge_dots() {
+    let mut a: VClock<&str> = Dot::new("a", 3).into();
+    a.merge_dots(vec![Dot::new("a", 2), Dot::new("a", 5), Dot::new("b", 1)]);
+
+    let expected: VClock<&str> = vec![Dot::new("a", 5), Dot::new("b", 1)]
+        .into_iter()
+        .collect();
+    assert_eq!(a, expected);
+}
+
 #[test]
 fn test_merge_less_left() {
     let (mut a, mut b) = (VClock::new(), VClock::new());
@@ -209,3 +631,281 @@ fn test_vclock_ordering() {
     assert!(!(a > b));
     assert_eq!(a, b);
 }
+
+#[test]
+fn test_varint_encoding_is_smaller_than_bincode_for_sparse_clocks() {
+    let clock: VClock<u64> = vec![Dot::new(7, 3), Dot::new(1024, 1), Dot::new(90_000, 12)]
+        .into_iter()
+        .collect();
+
+    let varint = clock.encode_varint();
+    let bincode = bincode::serialize(&clock).unwrap();
+
+    assert_eq!(VClock::decode_varint(&varint), Ok(clock));
+    assert!(varint.len() < bincode.len());
+}
+
+#[test]
+fn test_serialized_size_budget() {
+    let clock: VClock<u64> = vec![Dot::new(7, 3), Dot::new(1024, 1), Dot::new(90_000, 12)]
+        .into_iter()
+        .collect();
+
+    // bincode: an 8 byte length then 8 bytes per actor and per counter
+    assert!(bincode::serialized_size(&clock).unwrap() <= 8 + 3 * 16);
+    // varint: one byte for the length then each actor and counter in as few bytes as fit
+    assert!(clock.encode_varint().len() <= 10);
+}
+
+#[test]
+fn test_varint_decode_errors() {
+    let clock: VClock<u64> = vec![Dot::new(300, 2)].into_iter().collect();
+    let bytes = clock.encode_varint();
+
+    assert_eq!(
+        VClock::<u64>::decode_varint(&bytes[..bytes.len() - 1]),
+        Err(DecodeError::UnexpectedEnd)
+    );
+
+    let mut trailing = bytes.clone();
+    trailing.push(0);
+    assert_eq!(
+        VClock::<u64>::decode_varint(&trailing),
+        Err(DecodeError::TrailingBytes)
+    );
+
+    // actor 300 does not fit in a u8
+    assert_eq!(
+        VClock::<u8>::decode_varint(&bytes),
+        Err(DecodeError::InvalidActor)
+    );
+
+    // a varint with more than 64 bits of payload
+    let overflow = [
+        1, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x7f, 1,
+    ];
+    assert_eq!(
+        VClock::<u64>::decode_varint(&overflow),
+        Err(DecodeError::Overflow)
+    );
+}
+
+#[test]
+fn test_from_lenient_accepts_numeric_counters() {
+    use serde::de::value::{Error, MapDeserializer};
+
+    let entries = vec![("a".to_string(), 5u64), ("b".to_string(), 0u64)];
+    let clock: VClock<String> =
+        VClock::from_lenient(MapDeserializer::<_, Error>::new(entries.into_iter())).unwrap();
+
+    // zero counters are dropped, as with any other clock
+    assert_eq!(
+        clock,
+        vec![Dot::new("a".to_string(), 5)].into_iter().collect()
+    );
+}
+
+#[test]
+fn test_from_lenient_accepts_string_counters() {
+    use serde::de::value::{Error, MapDeserializer};
+
+    let entries = vec![("a".to_string(), "5"), ("b".to_string(), " 7 ")];
+    let clock: VClock<String> =
+        VClock::from_lenient(MapDeserializer::<_, Error>::new(entries.into_iter())).unwrap();
+
+    assert_eq!(clock.get(&"a".to_string()), 5);
+    assert_eq!(clock.get(&"b".to_string()), 7);
+}
+
+#[test]
+fn test_from_lenient_rejects_negative_counters() {
+    use serde::de::value::{Error, MapDeserializer};
+
+    let numeric = vec![("a".to_string(), -1i64)];
+    assert!(
+        VClock::<String>::from_lenient(MapDeserializer::<_, Error>::new(numeric.into_iter()))
+            .is_err()
+    );
+
+    let string = vec![("a".to_string(), "-1")];
+    assert!(
+        VClock::<String>::from_lenient(MapDeserializer::<_, Error>::new(string.into_iter()))
+            .is_err()
+    );
+}
+
+#[cfg(feature = "serde_json")]
+#[test]
+fn test_from_json_value_accepts_numeric_and_string_counters() {
+    let numeric: VClock<String> = VClock::from_json_value(&serde_json::json!({"a": 5})).unwrap();
+    assert_eq!(numeric.get(&"a".to_string()), 5);
+
+    let string: VClock<String> = VClock::from_json_value(&serde_json::json!({"a": "5"})).unwrap();
+    assert_eq!(string, numeric);
+}
+
+#[cfg(feature = "serde_json")]
+#[test]
+fn test_from_json_value_rejects_negative_counters() {
+    assert!(VClock::<String>::from_json_value(&serde_json::json!({"a": -1})).is_err());
+    assert!(VClock::<String>::from_json_value(&serde_json::json!({"a": "-1"})).is_err());
+    assert!(VClock::<String>::from_json_value(&serde_json::json!([["a", 1]])).is_err());
+}
+
+#[test]
+fn test_merge_reporting() {
+    let mut a: VClock<u8> = vec![Dot::new(1, 2)].into_iter().collect();
+    let b: VClock<u8> = vec![Dot::new(2, 1)].into_iter().collect();
+
+    assert_eq!(a.merge_reporting(&b), CausalOrder::Concurrent);
+    assert_eq!(
+        a,
+        vec![Dot::new(1, 2), Dot::new(2, 1)].into_iter().collect()
+    );
+
+    assert_eq!(a.merge_reporting(&b), CausalOrder::Greater);
+    assert_eq!(a.clone().merge_reporting(&a), CausalOrder::Equal);
+    assert_eq!(VClock::new().merge_reporting(&b), CausalOrder::Less);
+}
+
+#[test]
+fn test_merge_fast_forwards_to_checkpoint() {
+    let mut lagging: VClock<u8> = vec![Dot::new(1, 5), Dot::new(2, 1)].into_iter().collect();
+    let checkpoint: VClock<u8> = vec![Dot::new(2, 3), Dot::new(3, 2)].into_iter().collect();
+    assert_eq!(lagging.partial_cmp(&checkpoint), None);
+
+    lagging.merge(checkpoint.clone());
+    assert!(lagging >= checkpoint);
+    assert_eq!(
+        lagging,
+        vec![Dot::new(1, 5), Dot::new(2, 3), Dot::new(3, 2)]
+            .into_iter()
+            .collect()
+    );
+}
+
+#[test]
+fn test_sum() {
+    let clock: VClock<char> = vec![Dot::new('a', 3), Dot::new('b', 5)]
+        .into_iter()
+        .collect();
+    assert_eq!(clock.sum(), 8);
+    assert_eq!(VClock::<char>::new().sum(), 0);
+
+    let huge: VClock<char> = vec![Dot::new('a', u64::MAX), Dot::new('b', 1)]
+        .into_iter()
+        .collect();
+    assert_eq!(huge.sum(), u64::MAX);
+}
+
+#[test]
+fn test_deserialize_drops_zero_counters() {
+    // bincode encodes a single field struct as just that field
+    let dots: BTreeMap<u8, u64> = vec![(1, 3), (2, 0)].into_iter().collect();
+    let bytes = bincode::serialize(&dots).unwrap();
+
+    let clock: VClock<u8> = bincode::deserialize(&bytes).unwrap();
+    assert_eq!(clock, VClock::from(Dot::new(1, 3)));
+    assert_eq!(
+        clock.partial_cmp(&VClock::from(Dot::new(1, 3))),
+        Some(Ordering::Equal)
+    );
+}
+
+#[test]
+fn test_ack_prunes_pending_sends() {
+    let mut sent: VClock<u8> = vec![Dot::new(1, 3), Dot::new(2, 2)].into_iter().collect();
+    let peer: VClock<u8> = vec![Dot::new(2, 1)].into_iter().collect();
+    assert_eq!(sent.clone_without(&peer), sent);
+
+    sent.ack(&Dot::new(1, 3));
+    assert_eq!(sent.clone_without(&peer), VClock::from(Dot::new(2, 2)));
+
+    // an ack that does not cover the latest send leaves it pending
+    sent.ack(&Dot::new(2, 1));
+    assert_eq!(sent.clone_without(&peer), VClock::from(Dot::new(2, 2)));
+}
+
+#[test]
+fn test_contains_actor() {
+    let mut a: VClock<u8> = vec![Dot::new(1, 4), Dot::new(2, 3)].into_iter().collect();
+    assert!(a.contains_actor(&1));
+    assert!(!a.contains_actor(&3));
+
+    // forgetting actor 1's history drops it from the clock entirely
+    a.forget(&Dot::new(1, 4).into());
+    assert!(!a.contains_actor(&1));
+    assert!(a.contains_actor(&2));
+}
+
+#[test]
+fn test_apply_dot_returning_previous_counter() {
+    let mut a: VClock<u8> = vec![Dot::new(1, 2)].into_iter().collect();
+    assert_eq!(a.apply_dot_returning(Dot::new(1, 5)), 2);
+    assert_eq!(a.get(&1), 5);
+
+    // stale dots leave the clock untouched
+    assert_eq!(a.apply_dot_returning(Dot::new(1, 3)), 5);
+    assert_eq!(a.get(&1), 5);
+    assert_eq!(a.apply_dot_returning(Dot::new(2, 1)), 0);
+}
+
+#[test]
+fn test_partial_cmp_and_concurrent() {
+    let empty: VClock<u8> = VClock::new();
+    let a: VClock<u8> = vec![Dot::new(1, 2), Dot::new(2, 1)].into_iter().collect();
+    let b: VClock<u8> = vec![Dot::new(1, 3), Dot::new(2, 1)].into_iter().collect();
+    let c: VClock<u8> = vec![Dot::new(3, 1), Dot::new(4, 5)].into_iter().collect();
+
+    // the empty clock is dominated by every other clock
+    assert_eq!(empty.partial_cmp(&VClock::new()), Some(Ordering::Equal));
+    assert_eq!(empty.partial_cmp(&a), Some(Ordering::Less));
+    assert!(!empty.concurrent(&a));
+
+    assert_eq!(a.partial_cmp(&a.clone()), Some(Ordering::Equal));
+    assert!(!a.concurrent(&a.clone()));
+
+    assert_eq!(a.partial_cmp(&b), Some(Ordering::Less));
+    assert_eq!(b.partial_cmp(&a), Some(Ordering::Greater));
+    assert!(!a.concurrent(&b));
+
+    // clocks over disjoint actors have each seen something the other hasn't
+    assert_eq!(a.partial_cmp(&c), None);
+    assert!(a.concurrent(&c));
+    assert!(c.concurrent(&a));
+}
+
+#[test]
+fn test_dots_iterate_in_actor_order() {
+    let clock: VClock<u8> = vec![Dot::new(9, 1), Dot::new(2, 4), Dot::new(5, 3)]
+        .into_iter()
+        .collect();
+    let expected = vec![Dot::new(2, 4), Dot::new(5, 3), Dot::new(9, 1)];
+    assert_eq!(clock.dots().collect::<Vec<_>>(), expected);
+    assert_eq!(clock.clone().dots().collect::<Vec<_>>(), expected);
+}
+
+#[test]
+fn test_sorting_dots_is_deterministic() {
+    let a: VClock<u8> = vec![Dot::new(3, 2), Dot::new(1, 5)].into_iter().collect();
+    let b: VClock<u8> = vec![Dot::new(1, 2), Dot::new(2, 7)].into_iter().collect();
+
+    let sorted = |dots: Vec<Dot<u8>>| {
+        let mut dots: Vec<OrdDot<u8>> = dots.into_iter().map(OrdDot::from).collect();
+        dots.sort();
+        dots.into_iter().map(Dot::from).collect::<Vec<_>>()
+    };
+    let forward = sorted(a.dots().chain(b.dots()).collect());
+    let backward = sorted(b.dots().chain(a.dots()).collect());
+
+    assert_eq!(forward, backward);
+    assert_eq!(
+        forward,
+        vec![
+            Dot::new(1, 2),
+            Dot::new(1, 5),
+            Dot::new(2, 7),
+            Dot::new(3, 2)
+        ]
+    );
+}